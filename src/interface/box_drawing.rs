@@ -1,4 +1,7 @@
-#[derive(Copy, Clone)]
+use serde::Deserialize;
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BoxDrawing {
     Light,
     Heavy,
@@ -27,4 +30,20 @@ impl BoxDrawing {
             BoxDrawing::Arc =>                  {('─', '│', '╰', '╭', '╯', '╮')},
         }
     }
+
+    // cross, down tee, up tee, right tee, left tee
+    pub fn get_junction_char(typ: BoxDrawing) -> (char, char, char, char, char) {
+        match typ {
+            BoxDrawing::Light |
+            BoxDrawing::DoubleDashLight |
+            BoxDrawing::TripleDashLight |
+            BoxDrawing::QuadrupleDashLight |
+            BoxDrawing::Arc =>                  {('┼', '┬', '┴', '├', '┤')},
+            BoxDrawing::Heavy |
+            BoxDrawing::DoubleDashHeavy |
+            BoxDrawing::TripleDashHeavy |
+            BoxDrawing::QuadrupleDashHeavy =>   {('╋', '┳', '┻', '┣', '┫')},
+            BoxDrawing::Double =>               {('╬', '╦', '╩', '╠', '╣')},
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,52 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_error::UiError;
+
+type Handler = Box<dyn FnMut(&dyn Any, &mut VecDeque<UiAction>) -> Result<(), UiError>>;
+
+// dispatch events to the handlers subscribed to their type, handlers act on the screen by pushing actions
+pub struct EventBus {
+    handlers: HashMap<TypeId, Vec<(u64, Handler)>>,
+    next_id: u64,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus {
+            handlers: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    // give the id used to unsubscribe
+    pub fn subscribe<T: 'static>(&mut self, mut handler: impl FnMut(&T, &mut VecDeque<UiAction>) -> Result<(), UiError> + 'static) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let handler: Handler = Box::new(move |event: &dyn Any, action_queue: &mut VecDeque<UiAction>| {
+            match event.downcast_ref::<T>() {
+                Some(event) => handler(event, action_queue),
+                None => Ok(()),
+            }
+        });
+        self.handlers.entry(TypeId::of::<T>()).or_default().push((id, handler));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: u64) {
+        for handlers in self.handlers.values_mut() {
+            handlers.retain(|(handler_id, _)| *handler_id != id);
+        }
+    }
+
+    // every handler of the event type is called, their errors are written like the other ui errors
+    pub fn publish(&mut self, event: &dyn Any, action_queue: &mut VecDeque<UiAction>) {
+        if let Some(handlers) = self.handlers.get_mut(&event.type_id()) {
+            for (_, handler) in handlers.iter_mut() {
+                if let Err(error) = handler(event, action_queue) {
+                    action_queue.push_back(UiAction::WriteError(error));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::interface::ui_error::UiError;
+
+struct WatchedFile {
+    path: PathBuf,
+    // element receiving the content
+    id: u64,
+    modified: Option<SystemTime>,
+}
+
+// polls the modification time of the files bound to elements
+pub struct FileWatcher {
+    files: Vec<WatchedFile>,
+    // time in ms between two checks
+    pub interval: u128,
+    elapsed: u128,
+}
+
+impl FileWatcher {
+    pub fn new() -> FileWatcher {
+        FileWatcher {
+            files: Vec::new(),
+            interval: 500,
+            elapsed: 0,
+        }
+    }
+
+    // bind the file to the element and return its content, an element has at most one file
+    pub fn watch(&mut self, id: u64, path: PathBuf) -> Result<String, UiError> {
+        self.unwatch(id);
+        let modified = modified_time(&path);
+        let content = read(&path);
+        self.files.push(WatchedFile { path, id, modified });
+        content
+    }
+
+    pub fn unwatch(&mut self, id: u64) {
+        self.files.retain(|file| file.id != id);
+    }
+
+    // content of the files changed since the last check, a file failing to read is tried again once changed
+    pub fn poll(&mut self, delta_time: u128) -> Vec<(u64, PathBuf, Result<String, UiError>)> {
+        self.elapsed += delta_time;
+        if self.elapsed < self.interval {
+            return Vec::new();
+        }
+        self.elapsed = 0;
+        let mut changed = Vec::new();
+        for file in self.files.iter_mut() {
+            let modified = modified_time(&file.path);
+            if modified != file.modified {
+                file.modified = modified;
+                changed.push((file.id, file.path.clone(), read(&file.path)));
+            }
+        }
+        changed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn read(path: &Path) -> Result<String, UiError> {
+    fs::read_to_string(path).map_err(|error| UiError::Io(format!("{}: {}", path.display(), error)))
+}
+
+// prefix the error with the file it comes from
+pub fn in_file(error: UiError, path: &Path) -> UiError {
+    error.map_message(|message| format!("{}: {}", path.display(), message))
+}
@@ -0,0 +1,238 @@
+use serde::Deserialize;
+use crate::util::vector2::{Vector2};
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Constraint {
+    Fixed(i32),
+    // of the space available in the parent
+    Percentage(u32),
+    // at least the given size, grows like Fill(1)
+    Min(i32),
+    // grows like Fill(1) up to the given size
+    Max(i32),
+    // weighted share of the remaining space
+    Fill(u32),
+}
+
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    // children from left to right
+    Row,
+    // children from top to bottom
+    Column,
+}
+
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    Start,
+    Center,
+    End,
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct Spacing {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32,
+}
+
+impl Spacing {
+    pub fn new(left: i32, right: i32, top: i32, bottom: i32) -> Spacing {
+        Spacing { left, right, top, bottom }
+    }
+
+    pub fn all(value: i32) -> Spacing {
+        Spacing::new(value, value, value, value)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rect {
+    // bottom left corner, like UiElement::get_pos
+    pub pos: Vector2,
+    pub size: Vector2,
+}
+
+impl Rect {
+    pub fn new(pos: Vector2, size: Vector2) -> Rect {
+        Rect { pos, size }
+    }
+
+    fn shrink(&self, spacing: Spacing) -> Rect {
+        let size = Vector2::new(
+            (self.size.x - spacing.left - spacing.right).max(0),
+            (self.size.y - spacing.top - spacing.bottom).max(0),
+        );
+        Rect::new(Vector2::new(self.pos.x + spacing.left, self.pos.y + spacing.bottom), size)
+    }
+}
+
+pub struct Layout {
+    pub direction: Direction,
+    // size along the direction of the parent
+    pub constraint: Constraint,
+    // size across the direction of the parent
+    pub cross_constraint: Constraint,
+    pub padding: Spacing,
+    pub margin: Spacing,
+    // position of the children across the direction when they do not fill it
+    pub align: Alignment,
+    // position of the children along the direction when they do not fill it
+    pub justify: Alignment,
+    pub children: Vec<Layout>,
+    // id of the element placed in this node
+    pub element: Option<u64>,
+}
+
+impl Layout {
+    pub fn new(direction: Direction, constraint: Constraint, children: Vec<Layout>) -> Layout {
+        Layout {
+            direction,
+            constraint,
+            cross_constraint: Constraint::Fill(1),
+            padding: Spacing::default(),
+            margin: Spacing::default(),
+            align: Alignment::Start,
+            justify: Alignment::Start,
+            children,
+            element: None,
+        }
+    }
+
+    // compute the rectangle of every element of the tree, the root fills the given area
+    pub fn compute(&self, area: Rect) -> Vec<(u64, Rect)> {
+        let mut rects = Vec::new();
+        self.compute_node(area.shrink(self.margin), &mut rects);
+        rects
+    }
+
+    fn compute_node(&self, rect: Rect, rects: &mut Vec<(u64, Rect)>) {
+        let inner = rect.shrink(self.padding);
+        if let Some(id) = self.element {
+            rects.push((id, inner));
+        }
+        if self.children.is_empty() {
+            return;
+        }
+
+        let is_row = self.direction == Direction::Row;
+        let (main_length, cross_length) = if is_row { (inner.size.x, inner.size.y) } else { (inner.size.y, inner.size.x) };
+        let margins: Vec<(i32, i32)> = self.children.iter().map(|child| {
+            let margin = child.margin;
+            if is_row {
+                (margin.left + margin.right, margin.top + margin.bottom)
+            } else {
+                (margin.top + margin.bottom, margin.left + margin.right)
+            }
+        }).collect();
+        let available = (main_length - margins.iter().map(|(main, _)| main).sum::<i32>()).max(0);
+        let sizes = distribute(&self.children, available);
+
+        let used = sizes.iter().sum::<i32>() + margins.iter().map(|(main, _)| main).sum::<i32>();
+        let mut main_offset = align_offset(self.justify, main_length - used);
+        for (i, child) in self.children.iter().enumerate() {
+            let main_size = sizes[i];
+            let cross_available = (cross_length - margins[i].1).max(0);
+            let cross_size = resolve_cross(child.cross_constraint, cross_available);
+            let cross_offset = align_offset(self.align, cross_available - cross_size);
+            let margin = child.margin;
+            // rectangle including the margins of the child
+            let outer = if is_row {
+                let pos = Vector2::new(
+                    inner.pos.x + main_offset,
+                    inner.pos.y + inner.size.y - cross_offset - cross_size - margins[i].1,
+                );
+                Rect::new(pos, Vector2::new(main_size + margins[i].0, cross_size + margins[i].1))
+            } else {
+                let pos = Vector2::new(
+                    inner.pos.x + cross_offset,
+                    inner.pos.y + inner.size.y - main_offset - main_size - margins[i].0,
+                );
+                Rect::new(pos, Vector2::new(cross_size + margins[i].1, main_size + margins[i].0))
+            };
+            child.compute_node(outer.shrink(margin), rects);
+            main_offset += main_size + margins[i].0;
+        }
+    }
+}
+
+// size of each child along the direction
+fn distribute(children: &[Layout], available: i32) -> Vec<i32> {
+    let mut sizes: Vec<i32> = children.iter().map(|child| {
+        match child.constraint {
+            Constraint::Fixed(size) => size,
+            Constraint::Percentage(percentage) => available * percentage as i32 / 100,
+            Constraint::Min(size) => size,
+            Constraint::Max(_) | Constraint::Fill(_) => 0,
+        }
+    }).collect();
+    let weights: Vec<u32> = children.iter().map(|child| {
+        match child.constraint {
+            Constraint::Min(_) | Constraint::Max(_) => 1,
+            Constraint::Fill(weight) => weight,
+            _ => 0,
+        }
+    }).collect();
+
+    // the space left is shared between the growing children, a child reaching its max gives back the excess
+    let mut growing: Vec<usize> = (0..children.len()).filter(|i| weights[*i] > 0).collect();
+    let mut remaining = available - sizes.iter().sum::<i32>();
+    while remaining > 0 && !growing.is_empty() {
+        let total_weight: u32 = growing.iter().map(|i| weights[*i]).sum();
+        let mut distributed = 0;
+        let mut weight_seen = 0;
+        let mut capped = Vec::new();
+        for i in growing.iter() {
+            // cumulative rounding so the whole space is used
+            weight_seen += weights[*i];
+            let end = (remaining as i64 * weight_seen as i64 / total_weight as i64) as i32;
+            let mut share = end - distributed;
+            distributed = end;
+            if let Constraint::Max(max) = children[*i].constraint {
+                if sizes[*i] + share >= max {
+                    share = (max - sizes[*i]).max(0);
+                    capped.push(*i);
+                }
+            }
+            sizes[*i] += share;
+        }
+        remaining = available - sizes.iter().sum::<i32>();
+        if capped.is_empty() {
+            break;
+        }
+        growing.retain(|i| !capped.contains(i));
+    }
+
+    // not enough space, the last children are shrunk first
+    let mut overflow = sizes.iter().sum::<i32>() - available;
+    for size in sizes.iter_mut().rev() {
+        if overflow <= 0 {
+            break;
+        }
+        let shrink = overflow.min(*size);
+        *size -= shrink;
+        overflow -= shrink;
+    }
+    sizes
+}
+
+fn resolve_cross(constraint: Constraint, available: i32) -> i32 {
+    match constraint {
+        Constraint::Fixed(size) => size.min(available),
+        Constraint::Percentage(percentage) => available * percentage as i32 / 100,
+        Constraint::Min(_) | Constraint::Fill(_) => available,
+        Constraint::Max(size) => size.min(available),
+    }
+}
+
+fn align_offset(alignment: Alignment, free: i32) -> i32 {
+    match alignment {
+        Alignment::Start => 0,
+        Alignment::Center => free.max(0) / 2,
+        Alignment::End => free.max(0),
+    }
+}
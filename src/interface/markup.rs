@@ -0,0 +1,297 @@
+// inline markup of the texts, tags start with a backtick:
+// `cRRGGBB sets the foreground color, `c{name} uses a named color of the grid
+// `bRRGGBB and `b{name} set the background color
+// `sFLAGS adds attributes, b bold, i italic, u underline, f blink and r reverse
+// `lN links the following words to the action N of the element
+// `tN shows the tooltip N of the element when the following words are hovered
+// `kFLAGS resets the styles of the flags, c for the color, b for the background, l for the action, t for the tooltip and s for the attributes
+// `[ saves the current style and `] restores it, so nested styles end correctly
+// `` writes a backtick
+// a single space right after a tag only ends it and is not part of the text,
+// tags ending with a brace or a bracket are already closed and keep the space
+use crate::interface::ui_error::{Span, UiError};
+use crate::render::quad;
+use crate::util::rgba8::RGBA8;
+
+#[derive(Clone)]
+pub enum StyleColor {
+    Rgba(RGBA8),
+    // resolved with the named colors of the grid when drawn
+    Named(String),
+}
+
+#[derive(Clone)]
+pub enum Tag {
+    Color(StyleColor),
+    Background(StyleColor),
+    Attributes(u32),
+    Action(i32),
+    Tooltip(i32),
+    Clear { color: bool, background: bool, action: bool, tooltip: bool, attributes: bool },
+    Push,
+    Pop,
+}
+
+#[derive(Clone)]
+pub enum Token {
+    Char(char),
+    // whitespace other than a new line
+    Space,
+    NewLine,
+    Tag(Tag),
+}
+
+// style of a character, none keeps the color of the element
+#[derive(Clone, Default)]
+pub struct TextStyle {
+    pub fg_color: Option<StyleColor>,
+    pub bg_color: Option<StyleColor>,
+    pub action: Option<i32>,
+    pub tooltip: Option<i32>,
+    // bitmask of the attributes of render::quad
+    pub attributes: u32,
+}
+
+pub struct MarkupWord {
+    pub text: String,
+    // one style for each character of the text
+    pub styles: Vec<TextStyle>,
+    // style of the space following the word, none before a new line or at the end
+    pub space_style: Option<TextStyle>,
+}
+
+pub enum MarkupItem {
+    Word(MarkupWord),
+    NewLine,
+}
+
+struct Tokenizer {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Tokenizer {
+    fn new(text: &str) -> Tokenizer {
+        Tokenizer {
+            chars: text.chars().collect(),
+            index: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        }
+        else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    // span from the given position to the current one
+    fn span_from(&self, line: usize, column: usize) -> Span {
+        let length = if self.line == line { (self.column - column).max(1) } else { 1 };
+        Span { line, column, length }
+    }
+
+    fn error(&self, message: String, line: usize, column: usize) -> UiError {
+        UiError::Markup(message, self.span_from(line, column))
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, Span)>, UiError> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek() {
+            let (line, column) = (self.line, self.column);
+            self.next();
+            let token = match c {
+                '`' => match self.read_tag(line, column)? {
+                    Some(tag) => Token::Tag(tag),
+                    None => Token::Char('`'),
+                },
+                '\n' => Token::NewLine,
+                c if c.is_whitespace() => Token::Space,
+                c => Token::Char(c),
+            };
+            tokens.push((token, self.span_from(line, column)));
+        }
+        Ok(tokens)
+    }
+
+    // read a tag after its backtick, none for an escaped backtick
+    fn read_tag(&mut self, line: usize, column: usize) -> Result<Option<Tag>, UiError> {
+        let flag = match self.next() {
+            Some(flag) => flag,
+            None => return Err(self.error(String::from("Unexpected end of text after a backtick, use `` to write one"), line, column)),
+        };
+        let tag = match flag {
+            '`' => return Ok(None),
+            'c' => Tag::Color(self.read_color(line, column)?),
+            'b' => Tag::Background(self.read_color(line, column)?),
+            's' => {
+                let flags = self.read_while(|c| !c.is_whitespace(), usize::MAX);
+                if flags.is_empty() {
+                    return Err(self.error(String::from("Expected flags in attribute tag"), line, column));
+                }
+                let mut attributes = 0;
+                for flag in flags.chars() {
+                    attributes |= match flag {
+                        'b' => quad::BOLD,
+                        'i' => quad::ITALIC,
+                        'u' => quad::UNDERLINE,
+                        'f' => quad::BLINK,
+                        'r' => quad::REVERSE,
+                        _ => return Err(self.error(format!("Invalid attribute flag {}", flag), line, column)),
+                    };
+                }
+                Tag::Attributes(attributes)
+            }
+            '[' => return Ok(Some(Tag::Push)),
+            ']' => return Ok(Some(Tag::Pop)),
+            'l' => {
+                let number = self.read_while(|c| c.is_ascii_digit(), usize::MAX);
+                let action = number.parse::<i32>().map_err(|_| self.error(format!("Expected an action number in link tag, found \"{}\"", number), line, column))?;
+                Tag::Action(action)
+            }
+            't' => {
+                let number = self.read_while(|c| c.is_ascii_digit(), usize::MAX);
+                let tooltip = number.parse::<i32>().map_err(|_| self.error(format!("Expected a tooltip number in tooltip tag, found \"{}\"", number), line, column))?;
+                Tag::Tooltip(tooltip)
+            }
+            'k' => {
+                let flags = self.read_while(|c| !c.is_whitespace(), usize::MAX);
+                let mut color = false;
+                let mut background = false;
+                let mut action = false;
+                let mut tooltip = false;
+                let mut attributes = false;
+                for flag in flags.chars() {
+                    match flag {
+                        'c' => color = true,
+                        'b' => background = true,
+                        'l' => action = true,
+                        't' => tooltip = true,
+                        's' => attributes = true,
+                        _ => return Err(self.error(format!("Invalid clear flag {}", flag), line, column)),
+                    }
+                }
+                if flags.is_empty() {
+                    return Err(self.error(String::from("Expected flags in clear tag"), line, column));
+                }
+                Tag::Clear { color, background, action, tooltip, attributes }
+            }
+            _ => return Err(self.error(format!("Invalid flag {}", flag), line, column)),
+        };
+        // the space ending the tag, a named color is closed by its brace
+        let is_named = matches!(&tag, Tag::Color(StyleColor::Named(_)) | Tag::Background(StyleColor::Named(_)));
+        if !is_named && self.peek() == Some(' ') {
+            self.next();
+        }
+        Ok(Some(tag))
+    }
+
+    // hexadecimal color or name between braces
+    fn read_color(&mut self, line: usize, column: usize) -> Result<StyleColor, UiError> {
+        if self.peek() == Some('{') {
+            self.next();
+            let name = self.read_while(|c| c != '}' && c != '\n', usize::MAX);
+            if self.next() != Some('}') {
+                return Err(self.error(format!("Expected }} after the color name \"{}\"", name), line, column));
+            }
+            if name.is_empty() {
+                return Err(self.error(String::from("Expected a color name between the braces"), line, column));
+            }
+            return Ok(StyleColor::Named(name));
+        }
+        let hex: String = self.read_while(|c| c.is_ascii_hexdigit(), 6);
+        if hex.chars().count() < 6 {
+            return Err(self.error(format!("Expected 6 hexadecimal digits or a {{name}} in color tag, found \"{}\"", hex), line, column));
+        }
+        let color = RGBA8::from_hex_string(&hex).map_err(|error| self.error(format!("Invalid color {}: {}", hex, error), line, column))?;
+        Ok(StyleColor::Rgba(color))
+    }
+
+    fn read_while(&mut self, predicate: impl Fn(char) -> bool, max_length: usize) -> String {
+        let mut read = String::new();
+        while let Some(c) = self.peek() {
+            if !predicate(c) || read.chars().count() >= max_length {
+                break;
+            }
+            read.push(c);
+            self.next();
+        }
+        read
+    }
+}
+
+pub fn tokenize(text: &str) -> Result<Vec<(Token, Span)>, UiError> {
+    Tokenizer::new(text).tokenize()
+}
+
+// split the text in words and line breaks, tags change the style of the following characters even inside a word
+pub fn parse(text: &str) -> Result<Vec<MarkupItem>, UiError> {
+    let mut items = Vec::new();
+    let mut word = MarkupWord { text: String::new(), styles: Vec::new(), space_style: None };
+    let mut style = TextStyle::default();
+    let mut saved_styles: Vec<TextStyle> = Vec::new();
+    for (token, span) in tokenize(text)? {
+        match token {
+            Token::Char(c) => {
+                word.text.push(c);
+                word.styles.push(style.clone());
+            }
+            Token::Space | Token::NewLine => {
+                if !word.text.is_empty() {
+                    if let Token::Space = token {
+                        word.space_style = Some(style.clone());
+                    }
+                    let next = MarkupWord { text: String::new(), styles: Vec::new(), space_style: None };
+                    items.push(MarkupItem::Word(std::mem::replace(&mut word, next)));
+                }
+                if let Token::NewLine = token {
+                    items.push(MarkupItem::NewLine);
+                }
+            }
+            Token::Tag(Tag::Color(color)) => style.fg_color = Some(color),
+            Token::Tag(Tag::Background(color)) => style.bg_color = Some(color),
+            Token::Tag(Tag::Attributes(attributes)) => style.attributes |= attributes,
+            Token::Tag(Tag::Action(index)) => style.action = Some(index),
+            Token::Tag(Tag::Tooltip(index)) => style.tooltip = Some(index),
+            Token::Tag(Tag::Clear { color, background, action, tooltip, attributes }) => {
+                if color {
+                    style.fg_color = None;
+                }
+                if background {
+                    style.bg_color = None;
+                }
+                if action {
+                    style.action = None;
+                }
+                if tooltip {
+                    style.tooltip = None;
+                }
+                if attributes {
+                    style.attributes = 0;
+                }
+            }
+            Token::Tag(Tag::Push) => saved_styles.push(style.clone()),
+            Token::Tag(Tag::Pop) => {
+                style = saved_styles.pop().ok_or_else(|| UiError::Markup(String::from("`] without a matching `["), span))?;
+            }
+        }
+    }
+    if !word.text.is_empty() {
+        items.push(MarkupItem::Word(word));
+    }
+    Ok(items)
+}
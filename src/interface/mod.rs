@@ -6,3 +6,22 @@ pub mod word;
 pub mod ui_action;
 pub mod ui_error;
 pub mod ui_viewport;
+pub mod ui_list;
+pub mod ui_table;
+pub mod ui_tree;
+pub mod ui_tabs;
+pub mod ui_dialog;
+pub mod layout;
+pub mod ui_panel;
+pub mod event_bus;
+pub mod markup;
+pub mod screen_file;
+pub mod file_watcher;
+pub mod theme;
+pub mod scene_manager;
+pub mod tooltip;
+pub mod ui_window;
+pub mod ui_split;
+pub mod split_ratios;
+pub mod ui_console;
+pub mod toasts;
\ No newline at end of file
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use rand::{Rng, thread_rng};
+use serde::Deserialize;
+use crate::Application;
+use crate::interface::screen::Screen;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_error::UiError;
+use crate::render::quad::Quad;
+
+// effect shown when the top scene changes, durations in ms
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transition {
+    Cut,
+    // colors of the whole grid mixed together
+    Fade(u64),
+    // new scene uncovered from left to right
+    Wipe(u64),
+    // cells of the new scene shown in a random order
+    Dissolve(u64),
+}
+
+impl Transition {
+    pub fn get_duration(&self) -> u128 {
+        match self {
+            Transition::Cut => 0,
+            Transition::Fade(duration) | Transition::Wipe(duration) | Transition::Dissolve(duration) => *duration as u128,
+        }
+    }
+}
+
+pub type SceneHook = Box<dyn FnMut(&mut Screen) -> Result<(), UiError>>;
+// creates a scene from the shader program of the grids
+pub type SceneFactory = Box<dyn Fn(u32) -> Scene>;
+
+pub struct Scene {
+    pub screen: Screen,
+    // called when the scene becomes the top one, once pushed or once the scene above is popped
+    pub on_enter: Option<SceneHook>,
+    // called when the scene stops being the top one
+    pub on_exit: Option<SceneHook>,
+}
+
+impl Scene {
+    pub fn new(screen: Screen) -> Scene {
+        Scene {
+            screen,
+            on_enter: None,
+            on_exit: None,
+        }
+    }
+}
+
+// last frame of the previous scene, blended over the new one until the end of the transition
+struct ActiveTransition {
+    transition: Transition,
+    from: Vec<Quad>,
+    elapsed: u128,
+    // when each cell appears for a dissolve, between 0 and 1
+    thresholds: Vec<f32>,
+}
+
+// stack of scenes, only the top one is updated and drawn
+pub struct SceneManager {
+    scenes: Vec<Scene>,
+    // scenes the actions can open by name
+    factories: HashMap<String, SceneFactory>,
+    shader_program: u32,
+    transition: Option<ActiveTransition>,
+    // grid size given to every scene once the window is resized
+    grid_size: Option<(u32, u32)>,
+}
+
+impl SceneManager {
+    pub fn new(shader_program: u32) -> SceneManager {
+        SceneManager {
+            scenes: Vec::new(),
+            factories: HashMap::new(),
+            shader_program,
+            transition: None,
+            grid_size: None,
+        }
+    }
+
+    pub fn register(&mut self, name: &str, factory: impl Fn(u32) -> Scene + 'static) {
+        self.factories.insert(String::from(name), Box::new(factory));
+    }
+
+    // a new scene each time, the scenes keep no state once popped
+    pub fn create(&self, name: &str) -> Result<Scene, UiError> {
+        match self.factories.get(name) {
+            Some(factory) => {
+                let mut scene = factory(self.shader_program);
+                if let Some((grid_width, grid_height)) = self.grid_size {
+                    fit(&mut scene.screen, grid_width, grid_height);
+                }
+                Ok(scene)
+            }
+            None => Err(UiError::NotFound(format!("No scene named {name}"))),
+        }
+    }
+
+    pub fn push(&mut self, mut scene: Scene, transition: Transition) {
+        self.start_transition(transition);
+        if let Some(current) = self.scenes.last_mut() {
+            exit(current);
+        }
+        enter(&mut scene);
+        self.scenes.push(scene);
+    }
+
+    pub fn pop(&mut self, transition: Transition) -> Option<Scene> {
+        self.start_transition(transition);
+        let mut scene = self.scenes.pop()?;
+        exit(&mut scene);
+        if let Some(current) = self.scenes.last_mut() {
+            enter(current);
+        }
+        Some(scene)
+    }
+
+    pub fn replace(&mut self, mut scene: Scene, transition: Transition) -> Option<Scene> {
+        self.start_transition(transition);
+        let previous = self.scenes.pop().map(|mut previous| {
+            exit(&mut previous);
+            previous
+        });
+        enter(&mut scene);
+        self.scenes.push(scene);
+        previous
+    }
+
+    // the scenes below the top one are resized too, they are shown again once popped
+    pub fn resize(&mut self, grid_width: u32, grid_height: u32) {
+        self.grid_size = Some((grid_width, grid_height));
+        for scene in self.scenes.iter_mut() {
+            fit(&mut scene.screen, grid_width, grid_height);
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut Screen> {
+        self.scenes.last_mut().map(|scene| &mut scene.screen)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    // perform the scene actions of the previous frame, then update the top scene
+    pub fn update(&mut self, app: &Application) {
+        let requests: Vec<UiAction> = match self.scenes.last_mut() {
+            Some(scene) => scene.screen.scene_requests.drain(..).collect(),
+            None => Vec::new(),
+        };
+        for request in requests {
+            self.perform_request(request);
+        }
+
+        // no input until the transition ends
+        let transitioning = self.transition.is_some();
+        if let Some(scene) = self.scenes.last_mut() {
+            if transitioning {
+                scene.screen.update(&app.without_input());
+            } else {
+                scene.screen.update(app);
+            }
+        }
+        self.blend(app.delta_time);
+    }
+
+    fn perform_request(&mut self, request: UiAction) {
+        let result = match request {
+            UiAction::PushScene(name, transition) => self.create(&name).map(|scene| self.push(scene, transition)),
+            UiAction::PopScene(transition) => {
+                self.pop(transition);
+                Ok(())
+            }
+            UiAction::ReplaceScene(name, transition) => self.create(&name).map(|scene| {
+                self.replace(scene, transition);
+            }),
+            _ => Ok(()),
+        };
+        if let Err(error) = result {
+            if let Some(screen) = self.current() {
+                screen.action_queue.push_back(UiAction::WriteError(error));
+            }
+        }
+    }
+
+    // keep the last frame of the top scene, a new transition starts from the blended frame of the current one
+    fn start_transition(&mut self, transition: Transition) {
+        let from = match self.scenes.last() {
+            Some(scene) if transition.get_duration() > 0 => scene.screen.grid.get_quads().to_vec(),
+            _ => {
+                self.transition = None;
+                return;
+            }
+        };
+        let mut rng = thread_rng();
+        let thresholds = match transition {
+            Transition::Dissolve(_) => from.iter().map(|_| rng.gen::<f32>()).collect(),
+            _ => Vec::new(),
+        };
+        self.transition = Some(ActiveTransition { transition, from, elapsed: 0, thresholds });
+    }
+
+    fn blend(&mut self, delta_time: u128) {
+        let (active, scene) = match (&mut self.transition, self.scenes.last_mut()) {
+            (Some(active), Some(scene)) => (active, scene),
+            _ => {
+                self.transition = None;
+                return;
+            }
+        };
+        active.elapsed += delta_time;
+        let t = active.elapsed as f32 / active.transition.get_duration() as f32;
+        let grid = &mut scene.screen.grid;
+        // a resized scene cannot be blended with the previous frame
+        if t >= 1.0 || active.from.len() != grid.get_quads().len() {
+            self.transition = None;
+            return;
+        }
+        let width = grid.width as f32;
+        let grid_width = grid.width as i32;
+        match active.transition {
+            Transition::Cut => {}
+            Transition::Fade(_) => grid.blend_from(&active.from, |_| t),
+            Transition::Wipe(_) => grid.blend_from(&active.from, |pos| if (pos.x as f32) < t * width { 1.0 } else { 0.0 }),
+            Transition::Dissolve(_) => {
+                let thresholds = &active.thresholds;
+                grid.blend_from(&active.from, |pos| if thresholds[(pos.x + pos.y * grid_width) as usize] < t { 1.0 } else { 0.0 })
+            }
+        }
+    }
+}
+
+fn enter(scene: &mut Scene) {
+    if let Some(on_enter) = &mut scene.on_enter {
+        if let Err(error) = on_enter(&mut scene.screen) {
+            scene.screen.action_queue.push_back(UiAction::WriteError(error));
+        }
+    }
+}
+
+fn exit(scene: &mut Scene) {
+    if let Some(on_exit) = &mut scene.on_exit {
+        if let Err(error) = on_exit(&mut scene.screen) {
+            scene.screen.action_queue.push_back(UiAction::WriteError(error));
+        }
+    }
+}
+
+fn fit(screen: &mut Screen, grid_width: u32, grid_height: u32) {
+    if screen.grid_width != grid_width || screen.grid_height != grid_height {
+        screen.resize(grid_width, grid_height);
+    }
+}
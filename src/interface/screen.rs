@@ -1,25 +1,91 @@
-use std::collections::VecDeque;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::rc::Rc;
 use crate::{Application, Grid};
-use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::event_bus::EventBus;
+use crate::interface::file_watcher::{self, FileWatcher};
+use crate::interface::layout::{Layout, Rect};
+use crate::interface::screen_file::{self, ElementBuilder, ElementDefinition, ScreenRegistry};
+use crate::interface::split_ratios::SplitRatios;
+use crate::interface::theme::Theme;
+use crate::interface::toasts::Toasts;
+use crate::interface::tooltip::Tooltip;
 use crate::interface::ui_action::UiAction;
-use crate::interface::ui_element::UiElement;
+use crate::interface::ui_console::{LogLevel, UiConsole};
+use crate::interface::ui_dialog::UiDialog;
+use crate::interface::ui_element::{IdSource, UiElement};
 use crate::interface::ui_error::UiError;
+use crate::interface::ui_list::{SelectionMode, UiList, UiListItem};
+use crate::interface::ui_split::UiSplit;
+use crate::interface::ui_table::{ColumnWidth, UiTable, UiTableColumn};
+use crate::interface::ui_tabs::{UiTab, UiTabs};
 use crate::interface::ui_text::UiText;
+use crate::interface::ui_tree::{UiTree, UiTreeNode};
 use crate::interface::ui_viewport::UiViewport;
-use crate::util::rgba8::RGBA8;
+use crate::interface::ui_window::UiWindow;
 use crate::util::vector2::{Vector2};
 
+// id of an element of a known type, given to application code to find the element again
+pub struct ElementHandle<T> {
+    id: u64,
+    element_type: PhantomData<T>,
+}
+
+impl<T> ElementHandle<T> {
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T> Clone for ElementHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ElementHandle<T> {}
+
 pub struct Screen {
     pub grid: Grid,
     pub grid_width: u32,
     pub grid_height: u32,
     pub ui_elements: Vec<Box<dyn UiElement>>,
-    pub next_id: u64,
-    pub action_queue: VecDeque<UiAction>
+    // ids of the elements neither updated nor drawn
+    pub hidden: HashSet<u64>,
+    // place the elements from the grid size when set
+    pub layout: Option<Layout>,
+    // modal dialogs, only the last one receives the inputs
+    pub dialogs: Vec<UiDialog>,
+    // shared with the panels and tabs, see IdSource
+    pub ids: IdSource,
+    pub action_queue: VecDeque<UiAction>,
+    // receives the messages and the selection, activation and dialog actions
+    pub event_bus: EventBus,
+    // ids of the elements named in code or in a screen file
+    pub names: HashMap<String, u64>,
+    // files reloaded in the texts bound to them
+    pub file_watcher: FileWatcher,
+    // style of the elements added to the screen, see set_theme
+    pub theme: Theme,
+    // push, pop and replace actions, performed by the scene manager
+    pub scene_requests: VecDeque<UiAction>,
+    // markup shown when the element of the id is hovered, see set_tooltip
+    pub tooltips: HashMap<u64, String>,
+    pub tooltip: Tooltip,
+    // ratios of the named splits, see load_split_ratios
+    pub split_ratios: SplitRatios,
+    // errors and log messages of the screen, shown over the elements
+    pub console: UiConsole,
+    // notifications stacked in a corner over the elements, see UiAction::Toast
+    pub toasts: Toasts,
 }
 
 impl Screen {
-    pub fn new(shader_program: u32) -> Screen {
+    // screen without any element, with the default grid size and theme
+    pub fn empty(shader_program: u32) -> Screen {
         let mul = 5;
         let grid_width = 16 * 2 * mul;
         let grid_height = 9 * mul;
@@ -35,80 +101,370 @@ impl Screen {
             grid_width,
             grid_height,
             ui_elements,
-            next_id: 0,
+            hidden: HashSet::new(),
+            layout: None,
+            dialogs: Vec::new(),
+            ids: IdSource::new(),
             action_queue: VecDeque::new(),
+            event_bus: EventBus::new(),
+            names: HashMap::new(),
+            file_watcher: FileWatcher::new(),
+            theme: Theme::dark(),
+            scene_requests: VecDeque::new(),
+            tooltips: HashMap::new(),
+            tooltip: Tooltip::new(),
+            split_ratios: SplitRatios::new(),
+            console: UiConsole::new(),
+            toasts: Toasts::new(),
         };
+        screen.set_theme(Theme::dark());
+        screen
+    }
+
+    // demo screen with every kind of element
+    pub fn new(shader_program: u32) -> Screen {
+        let mut screen = Screen::empty(shader_program);
 
-        match UiText::new(String::from("Lorem `cff00ff `l0 ipsum `kl dolor `kc sit amet, consectetur adipiscing elit.\nSuspendisse mi nisl, porta at mollis sit amet, tempor id nunc.\nPellentesque mi nisi, congue a sem ut, vulputate fermentum lacus. Integer eu eleifend massa.\nUt eget porttitor sapien. Donec lacus elit, aliquet ut massa et, tristique imperdiet ex.\nVestibulum lectus massa, consequat a enim vel, volutpat maximus ligula.\nInteger viverra mollis consectetur."), Vector2::new(5, 40), Vector2::new(30, 10)) {
-            Ok(mut lorem_ipsum) => {
-                lorem_ipsum.set_box_drawing(true, BoxDrawing::Arc);
-                lorem_ipsum.actions.push(UiAction::AddUiText(String::from("Generated by action!"), Vector2::new(40, 30), Vector2::new(30, 10)));
-                let viewport = Box::new(UiViewport::new(Box::new(lorem_ipsum)));
-                screen.add_element(viewport);
+        // added before the list which shows and hides it
+        let mut tree = UiTree::new(tree_roots(), Vector2::new(0, 0), Vector2::new(0, 0));
+        tree.set_loader(|path: &[String]| {
+            let dir: PathBuf = path.iter().collect();
+            let mut entries = Vec::new();
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.file_type()?.is_dir() {
+                    entries.push(UiTreeNode::new_lazy(name));
+                }
+                else {
+                    entries.push(UiTreeNode::new(name, Vec::new()));
+                }
             }
-            Err(error) => {
-                screen.action_queue.push_back(UiAction::WriteError(error))
+            entries.sort_by(|a, b| a.text.cmp(&b.text));
+            Ok(entries)
+        });
+        let mut viewport = UiViewport::new(Box::new(tree));
+        viewport.keyboard_scroll = false;
+        let tree_viewport = screen.add(viewport);
+
+        let mut items: Vec<UiListItem> = (0..200).map(|i| UiListItem::new(format!("Item {i}"))).collect();
+        items[0].action = Some(UiAction::AddUiText(String::from("Activated from the list!"), Vector2::new(40, 20), Vector2::new(30, 1)));
+        items[1].action = Some(UiAction::ShowDialog(String::from("Dialog"), String::from("Opened from the list.\nPress escape or a button to close it."), vec![String::from("OK"), String::from("Cancel")]));
+        items[2] = UiListItem::new(String::from("Hide the tree"));
+        items[2].action = Some(UiAction::SetVisible(tree_viewport.get_id(), false));
+        items[3] = UiListItem::new(String::from("Show the tree"));
+        items[3].action = Some(UiAction::SetVisible(tree_viewport.get_id(), true));
+        let activations = Rc::new(Cell::new(0));
+        items[4] = UiListItem::new(String::from("Count activations"));
+        items[4].action = Some(UiAction::callback(move |screen: &mut Screen| {
+            activations.set(activations.get() + 1);
+            let body = format!("This item was activated {} times.", activations.get());
+            screen.perform_action(UiAction::ShowDialog(String::from("Callback"), body, vec![String::from("OK")]));
+            Ok(())
+        }));
+        items[5] = UiListItem::new(String::from("Send a message"));
+        items[5].action = Some(UiAction::message(String::from("Hello from the event bus!")));
+        let themes: [fn() -> Theme; 3] = [Theme::dark, Theme::light, Theme::high_contrast];
+        for (i, (name, theme)) in ["Dark theme", "Light theme", "High contrast theme"].into_iter().zip(themes).enumerate() {
+            items[6 + i] = UiListItem::new(String::from(name));
+            items[6 + i].action = Some(UiAction::callback(move |screen: &mut Screen| {
+                screen.set_theme(theme());
+                let message = format!("Switched to the {} theme", screen.theme.name);
+                screen.perform_action(UiAction::Toast(LogLevel::Info, message, None));
+                Ok(())
+            }));
+        }
+        items[9] = UiListItem::new(String::from("Show the windows"));
+        items[9].action = Some(UiAction::callback(|screen: &mut Screen| {
+            for name in ["tools_window", "notes_window"] {
+                if let Some(id) = screen.get_id_by_name(name) {
+                    screen.set_visible(id, true)?;
+                }
             }
+            Ok(())
+        }));
+        let levels = [LogLevel::Info, LogLevel::Warning, LogLevel::Error];
+        for (i, (name, level)) in ["Show an info toast", "Show a warning toast", "Show an error toast"].into_iter().zip(levels).enumerate() {
+            items[10 + i] = UiListItem::new(String::from(name));
+            items[10 + i].action = Some(UiAction::Toast(level, String::from("Click the toast to dismiss it, or wait for it to fade out."), None));
         }
-
-        match UiText::new(String::from(""), Vector2::new(1, 1), Vector2::new(10, 1)) {
-            Ok(mut delta_time) => {
-                delta_time.update_function = |ui_text: &mut UiText, app: &Application, _grid: &Grid| {
-                    let delta_time_str = format!("{} ms", app.delta_time);
-                    ui_text.set_text(delta_time_str)?;
-                    Ok(())
-                };
-                delta_time.set_box_drawing(true, BoxDrawing::Double);
-                screen.add_element(Box::new(delta_time));
+        items[15] = UiListItem::new(String::from("Reload the tree"));
+        items[15].action = Some(UiAction::callback(move |screen: &mut Screen| {
+            // the tree is inside a viewport, the failed loads are tried again
+            let tree = screen.get(tree_viewport)
+                .and_then(|viewport| viewport.get_children_mut().into_iter().next())
+                .and_then(|tree| tree.as_any_mut().downcast_mut::<UiTree>());
+            if let Some(tree) = tree {
+                tree.set_roots(tree_roots());
             }
-            Err(error) => {
-                screen.action_queue.push_back(UiAction::WriteError(error))
+            Ok(())
+        }));
+        items[16] = UiListItem::new(String::from("Close the active tab"));
+        items[16].action = Some(UiAction::callback(|screen: &mut Screen| {
+            let id = screen.get_id_by_name("tabs").ok_or_else(|| UiError::NotFound(String::from("No element named tabs")))?;
+            if let Some(tabs) = screen.get_as::<UiTabs>(id) {
+                tabs.close_tab(tabs.get_active());
             }
+            Ok(())
+        }));
+        let notes = Rc::new(Cell::new(0));
+        items[17] = UiListItem::new(String::from("Add a tab"));
+        items[17].action = Some(UiAction::callback(move |screen: &mut Screen| {
+            let id = screen.get_id_by_name("tabs").ok_or_else(|| UiError::NotFound(String::from("No element named tabs")))?;
+            notes.set(notes.get() + 1);
+            let content = UiText::new(format!("Note number {}, added after the tabs were on the screen.", notes.get()), Vector2::new(0, 0), Vector2::new(0, 0))?;
+            let mut tab = UiTab::new(format!("Note {}", notes.get()), Box::new(content));
+            tab.closable = true;
+            // the tab takes an id, the theme and the bounds of the tabs once added
+            let content_id = match screen.get_as::<UiTabs>(id) {
+                Some(tabs) => {
+                    tabs.add_tab(tab)?;
+                    let index = tabs.get_children_mut().len() - 1;
+                    tabs.set_active(index);
+                    tabs.get_children_mut()[index].get_id()
+                }
+                None => return Ok(()),
+            };
+            screen.set_tooltip(content_id, format!("Element {content_id}"));
+            Ok(())
+        }));
+        let subscription: Rc<Cell<Option<u64>>> = Rc::new(Cell::new(None));
+        items[18] = UiListItem::new(String::from("Log the element events"));
+        items[18].action = Some(UiAction::callback(move |screen: &mut Screen| {
+            match subscription.take() {
+                Some(id) => {
+                    screen.event_bus.unsubscribe(id);
+                    screen.perform_action(UiAction::Log(LogLevel::Info, String::from("Stopped logging the element events")));
+                }
+                None => {
+                    let id = screen.event_bus.subscribe(|action: &UiAction, action_queue: &mut VecDeque<UiAction>| {
+                        let message = match action {
+                            UiAction::SelectionChanged(id, selection) => format!("Selection of #{id}: {selection:?}"),
+                            UiAction::Activated(id, path) => format!("Activated in #{id}: {path:?}"),
+                            UiAction::DialogClosed(id, button) => format!("Dialog #{id} closed with the button {button:?}"),
+                            _ => return Ok(()),
+                        };
+                        action_queue.push_back(UiAction::Log(LogLevel::Info, message));
+                        Ok(())
+                    });
+                    subscription.set(Some(id));
+                }
+            }
+            Ok(())
+        }));
+        items[19] = UiListItem::new(String::from("Enable or disable the tools"));
+        items[19].action = Some(UiAction::callback(|screen: &mut Screen| {
+            let id = screen.get_id_by_name("tools_window").ok_or_else(|| UiError::NotFound(String::from("No element named tools_window")))?;
+            if let Some(window) = screen.get_as::<UiWindow>(id) {
+                window.enabled = !window.enabled;
+            }
+            Ok(())
+        }));
+        items[20] = UiListItem::new(String::from("Open a dialog with a disabled button"));
+        items[20].action = Some(UiAction::callback(|screen: &mut Screen| {
+            let grid_size = Vector2::new(screen.grid.width as i32, screen.grid.height as i32);
+            let buttons = vec![String::from("Save"), String::from("Delete"), String::from("Cancel")];
+            let mut dialog = UiDialog::new(String::from("Disabled"), String::from("The delete button cannot be pressed."), buttons, grid_size)?;
+            dialog.set_button_enabled(1, false);
+            dialog.set_theme(&screen.theme);
+            screen.open_dialog(dialog);
+            Ok(())
+        }));
+        screen.event_bus.subscribe(|message: &String, action_queue: &mut VecDeque<UiAction>| {
+            action_queue.push_back(UiAction::ShowDialog(String::from("Message"), message.clone(), vec![String::from("OK")]));
+            Ok(())
+        });
+        let mut list = UiList::new(items, Vector2::new(0, 0), Vector2::new(0, 0));
+        list.selection_mode = SelectionMode::Multi;
+        let mut viewport = UiViewport::new(Box::new(list));
+        viewport.keyboard_scroll = false;
+        let list = screen.add_element(Box::new(viewport));
+        screen.set_name("list", list);
+
+        let columns = vec![
+            UiTableColumn::new(String::from("Id"), ColumnWidth::Fixed(6)),
+            UiTableColumn::new(String::from("Name"), ColumnWidth::Proportional(2)),
+            UiTableColumn::new(String::from("Value"), ColumnWidth::Proportional(1)),
+        ];
+        let rows = (0..20000).map(|i| vec![
+            i.to_string(),
+            format!("Entry with a rather long name {}", (i * 7919) % 20000),
+            format!("{:.2}", ((i * 104729) % 10007) as f32 / 100.0),
+        ]).collect();
+        let table = UiTable::new(columns, rows, Vector2::new(0, 0), Vector2::new(0, 0));
+        let mut viewport = UiViewport::new(Box::new(table));
+        viewport.keyboard_scroll = false;
+        // columns resized wider than the table are scrolled horizontally
+        if let Err(error) = viewport.set_scrollbars(true, true) {
+            screen.action_queue.push_back(UiAction::WriteError(error))
         }
+        let table = screen.add_element(Box::new(viewport));
+        screen.set_name("table", table);
+        screen.set_tooltip(table, String::from("20000 generated rows, only the visible ones are drawn"));
+        screen.set_name("tree", tree_viewport.get_id());
 
-        match UiText::new(String::from(""), Vector2::new(0, 3), Vector2::new(30, 1)) {
-            Ok(mut mouse_pos) => {
-                mouse_pos.update_function = |ui_text: &mut UiText, app: &Application, _grid: &Grid| {
-                    let mouse_pos_str = format!("Mouse coordinate: {}, {}", app.cursor_position.x, app.cursor_position.y);
-                    ui_text.set_text(mouse_pos_str)?;
-                    Ok(())
-                };
-                screen.add_element(Box::new(mouse_pos));
+        let mut tabs = UiTabs::new(Vec::new(), Vector2::new(0, 0), Vector2::new(0, 0));
+        let items = (0..50).map(|i| UiListItem::new(format!("Number {}", i * i))).collect();
+        if let Err(error) = tabs.add_tab(UiTab::new(String::from("Numbers"), Box::new(UiList::new(items, Vector2::new(0, 0), Vector2::new(0, 0))))) {
+            screen.action_queue.push_back(UiAction::WriteError(error))
+        }
+        let lines: Vec<String> = (0..100).map(|i| format!("{i:>3} {}", "long line without wrapping, scroll with shift and the wheel or the bottom scrollbar. ".repeat(3))).collect();
+        match UiText::new(lines.join("\n"), Vector2::new(0, 0), Vector2::new(0, 0)) {
+            Ok(mut content) => {
+                let result = content.set_wrap(false).and_then(|_| {
+                    let mut viewport = UiViewport::new(Box::new(content));
+                    viewport.set_scrollbars(true, true)?;
+                    tabs.add_tab(UiTab::new(String::from("Wide"), Box::new(viewport)))
+                });
+                if let Err(error) = result {
+                    screen.action_queue.push_back(UiAction::WriteError(error))
+                }
             }
             Err(error) => {
                 screen.action_queue.push_back(UiAction::WriteError(error))
             }
         }
-
-        match UiText::new(String::from(""), Vector2::new(0, 4), Vector2::new(30, 1)) {
-            Ok(mut grid_pos) => {
-                grid_pos.update_function = |ui_text: &mut UiText, app: &Application, grid: &Grid| {
-                    let grid_pos_x = (app.cursor_position.x / app.width as f64 * grid.width as f64).floor() as i32;
-                    let grid_pos_y = (app.cursor_position.y / app.height as f64 * grid.height as f64).floor() as i32;
-                    let mouse_pos_str = format!("Grid coordinate: {grid_pos_x}, {grid_pos_y}");
-                    ui_text.set_text(mouse_pos_str)?;
-                    Ok(())
-                };
-                screen.add_element(Box::new(grid_pos));
+        for i in 1..8 {
+            match UiText::new(format!("Content of the tab number {i}, use ctrl+tab to switch."), Vector2::new(0, 0), Vector2::new(0, 0)) {
+                Ok(content) => {
+                    let mut tab = UiTab::new(format!("Tab {i}"), Box::new(content));
+                    tab.closable = true;
+                    if let Err(error) = tabs.add_tab(tab) {
+                        screen.action_queue.push_back(UiAction::WriteError(error))
+                    }
+                }
+                Err(error) => {
+                    screen.action_queue.push_back(UiAction::WriteError(error))
+                }
             }
-            Err(error) => {
-                screen.action_queue.push_back(UiAction::WriteError(error))
+        }
+        let tabs = screen.add_element(Box::new(tabs));
+        screen.set_name("tabs", tabs);
+
+        // the text, the readouts and the layout are described in the file
+        let mut registry = ScreenRegistry::new();
+        registry.add_update_function("delta_time", update_delta_time);
+        registry.add_update_function("mouse_position", update_mouse_position);
+        registry.add_update_function("grid_position", update_grid_position);
+        if let Err(error) = screen.load_file("screens/main.toml", &registry) {
+            screen.action_queue.push_back(UiAction::WriteError(error))
+        }
+        if let Err(error) = screen.load_split_ratios(PathBuf::from("split_ratios.toml")) {
+            screen.action_queue.push_back(UiAction::WriteError(error))
+        }
+
+        // floating windows, added last to be drawn over the layout
+        let items = ["Brush", "Eraser", "Fill", "Picker", "Line", "Rectangle"].into_iter().map(|tool| UiListItem::new(String::from(tool))).collect();
+        let tools = UiList::new(items, Vector2::new(0, 0), Vector2::new(0, 0));
+        let notes = UiText::new(String::from("Drag the title bar to move the window and its edges to resize it. The buttons minimize and close it."), Vector2::new(0, 0), Vector2::new(0, 0));
+        let windows = notes.and_then(|notes| {
+            let tools = UiWindow::new(String::from("Tools"), Box::new(tools), Vector2::new(104, 20), Vector2::new(20, 8))?;
+            let notes = UiWindow::new(String::from("Notes"), Box::new(notes), Vector2::new(118, 14), Vector2::new(30, 7))?;
+            Ok([("tools_window", tools), ("notes_window", notes)])
+        });
+        match windows {
+            Ok(windows) => {
+                for (name, window) in windows {
+                    let id = screen.add_element(Box::new(window));
+                    screen.set_name(name, id);
+                }
             }
+            Err(error) => screen.action_queue.push_back(UiAction::WriteError(error)),
         }
+        screen.action_queue.push_back(UiAction::Log(LogLevel::Info, String::from("F12 shows or hides the console")));
 
         screen
     }
 
     pub fn update(&mut self, app: &Application) {
         self.grid.clear();
+        self.reload_files(app.delta_time);
 
-        for ui_element in self.ui_elements.as_mut_slice() {
-            if let Err(error) = ui_element.update(app, &self.grid, &mut self.action_queue) {
-                self.action_queue.push_back(UiAction::WriteError(error));
+        // the inputs are captured by the dialogs
+        let app_without_input;
+        let elements_app = if self.dialogs.is_empty() {
+            app
+        } else {
+            app_without_input = app.without_input();
+            &app_without_input
+        };
+
+        // the console is over the elements, which do not receive the mouse under it
+        if let Err(error) = self.console.update(elements_app, &self.grid, &mut self.action_queue) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+        // same for the toasts, checked before a click dismisses the one under the cursor
+        let on_toast = self.toasts.is_mouse_on(elements_app);
+        if let Err(error) = self.toasts.update(elements_app, &self.grid) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+        let app_without_console;
+        let elements_app = if on_toast || self.console.is_mouse_on_element(elements_app, &self.grid) {
+            app_without_console = elements_app.without_mouse();
+            &app_without_console
+        } else {
+            elements_app
+        };
+
+        // the elements below the topmost one under the cursor, like a floating window, do not receive the mouse
+        let hovered = self.ui_elements.iter().rposition(|ui_element| {
+            !self.hidden.contains(&ui_element.get_id()) && ui_element.is_mouse_on_element(elements_app, &self.grid)
+        });
+        let app_without_mouse = elements_app.without_mouse();
+
+        for (index, ui_element) in self.ui_elements.iter_mut().enumerate() {
+            let id = ui_element.get_id();
+            if self.hidden.contains(&id) {
+                continue;
+            }
+            let element_app = if hovered.is_some_and(|hovered| index < hovered) { &app_without_mouse } else { elements_app };
+            if let Err(error) = ui_element.update(element_app, &self.grid, &mut self.action_queue) {
+                self.action_queue.push_back(UiAction::WriteError(error.in_element(id)));
             }
             if let Err(error) = ui_element.draw(&mut self.grid) {
-                self.action_queue.push_back(UiAction::WriteError(error));
+                self.action_queue.push_back(UiAction::WriteError(error.in_element(id)));
+            }
+        }
+
+        if let Err(error) = self.console.draw(&mut self.grid) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+
+        // the topmost element under the cursor gives the tooltip
+        let mut tooltip = None;
+        for ui_element in self.ui_elements.iter_mut().rev() {
+            if self.hidden.contains(&ui_element.get_id()) || !ui_element.is_mouse_on_element(elements_app, &self.grid) {
+                continue;
+            }
+            tooltip = find_tooltip(ui_element, elements_app, &self.grid, &self.tooltips);
+            break;
+        }
+        if let Err(error) = self.tooltip.update(tooltip.as_ref(), elements_app, &self.grid) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+
+        if !self.dialogs.is_empty() {
+            self.grid.dim(0.4);
+            let last = self.dialogs.len() - 1;
+            for (i, dialog) in self.dialogs.iter_mut().enumerate() {
+                let id = dialog.get_id();
+                if i == last {
+                    if let Err(error) = dialog.update(app, &self.grid, &mut self.action_queue) {
+                        self.action_queue.push_back(UiAction::WriteError(error.in_element(id)));
+                    }
+                }
+                if let Err(error) = dialog.draw(&mut self.grid) {
+                    self.action_queue.push_back(UiAction::WriteError(error.in_element(id)));
+                }
             }
+            self.dialogs.retain(|dialog| !dialog.is_closed());
+        }
+
+        if let Err(error) = self.toasts.draw(&mut self.grid) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+        if let Err(error) = self.tooltip.draw(&mut self.grid) {
+            self.action_queue.push_back(UiAction::WriteError(error));
         }
 
         // read action queue
@@ -121,10 +477,277 @@ impl Screen {
         // }
     }
 
-    pub fn add_element(&mut self, mut ui_element: Box<dyn UiElement>) {
-        ui_element.set_id(self.next_id);
-        self.next_id += 1;
+    // the element takes the style of the theme, the children get their own id
+    pub fn add_element(&mut self, mut ui_element: Box<dyn UiElement>) -> u64 {
+        ui_element.set_theme(&self.theme);
+        self.insert_element(ui_element)
+    }
+
+    // add the element keeping its colors
+    fn insert_element(&mut self, mut ui_element: Box<dyn UiElement>) -> u64 {
+        let id = self.ids.assign(&mut ui_element);
         self.ui_elements.push(ui_element);
+        id
+    }
+
+    pub fn add<T: UiElement + 'static>(&mut self, ui_element: T) -> ElementHandle<T> {
+        let id = self.add_element(Box::new(ui_element));
+        ElementHandle { id, element_type: PhantomData }
+    }
+
+    pub fn get<T: UiElement + 'static>(&mut self, handle: ElementHandle<T>) -> Option<&mut T> {
+        self.get_as(handle.id)
+    }
+
+    // element with the given id if it has the given type
+    pub fn get_as<T: UiElement + 'static>(&mut self, id: u64) -> Option<&mut T> {
+        self.find_element(id)?.as_any_mut().downcast_mut::<T>()
+    }
+
+    // search the elements of the screen and their children
+    pub fn find_element(&mut self, id: u64) -> Option<&mut Box<dyn UiElement>> {
+        self.ui_elements.iter_mut().find_map(|ui_element| find_in(ui_element, id))
+    }
+
+    // the files, tooltips and names of the element and its children are dropped with it
+    pub fn remove_element(&mut self, id: u64) -> Option<Box<dyn UiElement>> {
+        let mut ui_element = match self.ui_elements.iter().position(|ui_element| ui_element.get_id() == id) {
+            Some(index) => self.ui_elements.remove(index),
+            None => self.ui_elements.iter_mut().find_map(|ui_element| remove_in(ui_element, id))?,
+        };
+        let mut ids = Vec::new();
+        collect_ids(&mut ui_element, &mut ids);
+        for id in ids {
+            self.hidden.remove(&id);
+            self.file_watcher.unwatch(id);
+            self.tooltips.remove(&id);
+            self.names.retain(|_, named| *named != id);
+        }
+        Some(ui_element)
+    }
+
+    // only the elements of the screen can be hidden, the children are shown by their parent
+    pub fn set_visible(&mut self, id: u64, visible: bool) -> Result<(), UiError> {
+        if !self.ui_elements.iter().any(|ui_element| ui_element.get_id() == id) {
+            return Err(UiError::NotFound(format!("No element with id {id} on the screen to show or hide")));
+        }
+        if visible {
+            self.hidden.remove(&id);
+        }
+        else {
+            self.hidden.insert(id);
+        }
+        Ok(())
+    }
+
+    // elements are updated and drawn in order, the last one is on top
+    pub fn move_element(&mut self, id: u64, index: usize) -> bool {
+        match self.ui_elements.iter().position(|ui_element| ui_element.get_id() == id) {
+            Some(current) => {
+                let ui_element = self.ui_elements.remove(current);
+                let index = index.min(self.ui_elements.len());
+                self.ui_elements.insert(index, ui_element);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // a split takes the ratio saved under its name
+    pub fn set_name(&mut self, name: &str, id: u64) {
+        self.names.insert(String::from(name), id);
+        if let Err(error) = self.apply_split_ratio(name, id) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+    }
+
+    pub fn get_id_by_name(&self, name: &str) -> Option<u64> {
+        self.names.get(name).copied()
+    }
+
+    pub fn get_name(&self, id: u64) -> Option<&String> {
+        self.names.iter().find(|(_, named)| **named == id).map(|(name, _)| name)
+    }
+
+    // add the elements of a screen file, then replace the layout if the file has one
+    pub fn load_file(&mut self, path: &str, registry: &ScreenRegistry) -> Result<(), UiError> {
+        let text = fs::read_to_string(path)?;
+        let file = screen_file::parse(&text).map_err(|error| error.map_message(|message| format!("{path}: {message}")))?;
+        for (name, color) in file.colors.iter() {
+            self.grid.set_named_color(name.clone(), screen_file::parse_color(color)?);
+        }
+        for definition in file.elements.iter() {
+            let ui_element = ElementBuilder { registry, theme: &self.theme, names: &self.names }.build(definition)?;
+            let id = self.insert_element(ui_element);
+            self.apply_definition(id, definition);
+            if definition.hidden {
+                self.set_visible(id, false)?;
+            }
+            if let Some(file) = &definition.file {
+                // the text may be inside a viewport
+                let text_id = self.find_element(id).and_then(find_text_id).unwrap_or(id);
+                self.bind_text_file(text_id, PathBuf::from(file))?;
+            }
+        }
+        if let Some(layout) = &file.layout {
+            let layout = ElementBuilder { registry, theme: &self.theme, names: &self.names }.build_layout(layout)?;
+            self.set_layout(layout);
+        }
+        Ok(())
+    }
+
+    // the text shows the content of the file and is updated when the file changes
+    pub fn bind_text_file(&mut self, id: u64, path: PathBuf) -> Result<(), UiError> {
+        if self.get_as::<UiText>(id).is_none() {
+            return Err(UiError::NotFound(format!("No text with id {id} to bind to {}", path.display())));
+        }
+        let text = self.file_watcher.watch(id, path.clone())?;
+        match self.get_as::<UiText>(id) {
+            Some(ui_text) => ui_text.set_text(text).map_err(|error| file_watcher::in_file(error, &path)),
+            None => Ok(()),
+        }
+    }
+
+    // the scroll offsets and the focus are kept since the elements stay the same
+    fn reload_files(&mut self, delta_time: u128) {
+        for (id, path, result) in self.file_watcher.poll(delta_time) {
+            // a file saved without changes does not build the words again
+            let result = result.and_then(|text| match self.get_as::<UiText>(id) {
+                Some(ui_text) if *ui_text.get_text() == text => Ok(()),
+                Some(ui_text) => ui_text.set_text(text).map_err(|error| file_watcher::in_file(error, &path)),
+                None => Err(UiError::NotFound(format!("No text with id {id} to reload {}", path.display()))),
+            });
+            if let Err(error) = result {
+                self.action_queue.push_back(UiAction::WriteError(error.in_element(id)));
+            }
+        }
+    }
+
+    // restyle every element and dialog, the colors set on them since the last theme are replaced
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.grid.named_colors.extend(theme.colors.iter().map(|(name, color)| (name.clone(), *color)));
+        self.grid.default_fg_color = theme.background.fg_color;
+        self.grid.default_bg_color = theme.background.bg_color;
+        for ui_element in self.ui_elements.iter_mut() {
+            ui_element.set_theme(&theme);
+        }
+        for dialog in self.dialogs.iter_mut() {
+            dialog.set_theme(&theme);
+        }
+        self.tooltip.set_theme(&theme);
+        self.console.set_theme(&theme);
+        self.toasts.set_theme(&theme);
+        self.theme = theme;
+    }
+
+    // markup shown in a popup once the element is hovered for a while, over the one of its parents
+    pub fn set_tooltip(&mut self, id: u64, text: String) {
+        self.tooltips.insert(id, text);
+    }
+
+    // names and tooltips of an element built from a screen file, the children of panels and splits included
+    fn apply_definition(&mut self, id: u64, definition: &ElementDefinition) {
+        if let Some(name) = &definition.name {
+            self.set_name(name, id);
+        }
+        if let Some(tooltip) = &definition.tooltip {
+            self.set_tooltip(id, tooltip.clone());
+        }
+        let child_ids: Vec<u64> = match self.find_element(id) {
+            Some(ui_element) => {
+                // the children are inside the viewport wrapping the element
+                let parent = if definition.viewport { ui_element.get_children_mut().into_iter().next() } else { Some(ui_element) };
+                parent.map(|parent| parent.get_children_mut().iter().map(|child| child.get_id()).collect()).unwrap_or_default()
+            }
+            None => Vec::new(),
+        };
+        for (child_id, child) in child_ids.into_iter().zip(definition.children.iter()) {
+            self.apply_definition(child_id, child);
+        }
+    }
+
+    // the named splits take the ratios of the file, which is written each time one of their dividers is released
+    pub fn load_split_ratios(&mut self, path: PathBuf) -> Result<(), UiError> {
+        self.split_ratios.load(path)?;
+        let names: Vec<(String, u64)> = self.names.iter().map(|(name, id)| (name.clone(), *id)).collect();
+        for (name, id) in names {
+            self.apply_split_ratio(&name, id)?;
+        }
+        Ok(())
+    }
+
+    fn apply_split_ratio(&mut self, name: &str, id: u64) -> Result<(), UiError> {
+        let ratio = match self.split_ratios.get(name) {
+            Some(ratio) => ratio,
+            None => return Ok(()),
+        };
+        // the children are only placed again when the ratio changes
+        match self.get_as::<UiSplit>(id) {
+            Some(split) if split.get_ratio() != ratio => split.set_ratio(ratio),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+        self.apply_layout();
+    }
+
+    pub fn apply_layout(&mut self) {
+        let area = Rect::new(Vector2::new(0, 0), Vector2::new(self.grid_width as i32, self.grid_height as i32));
+        let rects = match &self.layout {
+            Some(layout) => layout.compute(area),
+            None => return,
+        };
+        for (id, rect) in rects {
+            if let Some(ui_element) = self.ui_elements.iter_mut().find(|ui_element| ui_element.get_id() == id) {
+                if let Err(error) = ui_element.set_bounds(rect.pos, rect.size) {
+                    self.action_queue.push_back(UiAction::WriteError(error));
+                }
+            }
+        }
+    }
+
+    // recreate the grid with a new size and place the elements again
+    pub fn resize(&mut self, grid_width: u32, grid_height: u32) {
+        let named_colors = std::mem::take(&mut self.grid.named_colors);
+        let (fg_color, bg_color) = (self.grid.default_fg_color, self.grid.default_bg_color);
+        self.grid = Grid::new(grid_width, grid_height, self.grid.get_program());
+        self.grid.named_colors = named_colors;
+        self.grid.default_fg_color = fg_color;
+        self.grid.default_bg_color = bg_color;
+        self.grid_width = grid_width;
+        self.grid_height = grid_height;
+        self.apply_layout();
+        let grid_size = Vector2::new(grid_width as i32, grid_height as i32);
+        for dialog in self.dialogs.iter_mut() {
+            if let Err(error) = dialog.center(grid_size) {
+                self.action_queue.push_back(UiAction::WriteError(error));
+            }
+        }
+        // the toasts stay in their corner
+        if let Err(error) = self.toasts.place(&self.grid) {
+            self.action_queue.push_back(UiAction::WriteError(error));
+        }
+        // the windows are not placed by the layout
+        for ui_element in self.ui_elements.iter_mut() {
+            if let Some(window) = ui_element.as_any_mut().downcast_mut::<UiWindow>() {
+                if let Err(error) = window.fit_in(grid_size) {
+                    self.action_queue.push_back(UiAction::WriteError(error.in_element(window.get_id())));
+                }
+            }
+        }
+    }
+
+    pub fn open_dialog(&mut self, mut dialog: UiDialog) -> u64 {
+        let id = self.ids.next();
+        dialog.set_id(id);
+        self.dialogs.push(dialog);
+        id
+    }
+
+    pub fn has_dialog(&self) -> bool {
+        !self.dialogs.is_empty()
     }
 
     pub fn perform_action(&mut self, action: UiAction) {
@@ -140,17 +763,136 @@ impl Screen {
                 }
             }
             UiAction::WriteError(error) => {
-                match error {
-                    UiError::Error(text) => {
-                        let len = text.len() as i32;
-                        let pos = Vector2::new(self.grid.width as i32 / 2 - len / 2, (self.grid.height as f32 * 0.85).floor() as i32);
-                        let mut error_element = UiText::new(text, pos, Vector2::new(len, 1)).unwrap();
-                        error_element.fg_color = RGBA8::new(255, 0, 0, 255);
-                        error_element.set_box_drawing(true, BoxDrawing::Double);
-                        self.add_element(Box::new(error_element));
+                // an element failing every frame only counts the repetitions of its line
+                let source = error.get_source().map(|id| self.get_name(id).cloned().unwrap_or_else(|| format!("#{id}")));
+                self.console.log_error(&error, source);
+            }
+            UiAction::Log(level, message) => {
+                let label = String::from(level.get_name());
+                self.console.log(level, label, None, message);
+            }
+            UiAction::Toast(level, text, duration) => {
+                if let Err(error) = self.toasts.show(level, text, duration, &self.grid) {
+                    self.action_queue.push_back(UiAction::WriteError(error));
+                }
+            }
+            UiAction::ShowDialog(title, body, buttons) => {
+                let grid_size = Vector2::new(self.grid.width as i32, self.grid.height as i32);
+                match UiDialog::new(title, body, buttons, grid_size) {
+                    Ok(mut dialog) => {
+                        dialog.set_theme(&self.theme);
+                        self.open_dialog(dialog);
+                    }
+                    Err(error) => {
+                        self.action_queue.push_back(UiAction::WriteError(error));
+                    }
+                }
+            }
+            UiAction::RemoveElement(id) => {
+                if self.remove_element(id).is_none() {
+                    self.action_queue.push_back(UiAction::WriteError(UiError::NotFound(format!("No element with id {id} to remove"))));
+                }
+            }
+            UiAction::SetVisible(id, visible) => {
+                if let Err(error) = self.set_visible(id, visible) {
+                    self.action_queue.push_back(UiAction::WriteError(error));
+                }
+            }
+            UiAction::MoveElement(id, index) => {
+                if !self.move_element(id, index) {
+                    self.action_queue.push_back(UiAction::WriteError(UiError::NotFound(format!("No element with id {id} on the screen to move"))));
+                }
+            }
+            UiAction::SplitMoved(id, ratio) => {
+                if let Some(name) = self.get_name(id).cloned() {
+                    if let Err(error) = self.split_ratios.set(name, ratio) {
+                        self.action_queue.push_back(UiAction::WriteError(error));
                     }
                 }
             }
+            UiAction::SetText(id, text) => {
+                let result = match self.get_as::<UiText>(id) {
+                    Some(ui_text) => ui_text.set_text(text),
+                    None => Err(UiError::NotFound(format!("No text element with id {id}"))),
+                };
+                if let Err(error) = result {
+                    self.action_queue.push_back(UiAction::WriteError(error));
+                }
+            }
+            UiAction::Callback(callback) => {
+                if let Err(error) = callback(self) {
+                    self.action_queue.push_back(UiAction::WriteError(error));
+                }
+            }
+            UiAction::Message(message) => {
+                self.event_bus.publish(message.as_ref(), &mut self.action_queue);
+            }
+            UiAction::PushScene(_, _) | UiAction::PopScene(_) | UiAction::ReplaceScene(_, _) => {
+                self.scene_requests.push_back(action);
+            }
+            UiAction::SelectionChanged(_, _) | UiAction::Activated(_, _) | UiAction::DialogClosed(_, _) => {
+                // only meaningful to application code
+                self.event_bus.publish(&action, &mut self.action_queue);
+            }
+        }
+    }
+}
+
+fn find_in(ui_element: &mut Box<dyn UiElement>, id: u64) -> Option<&mut Box<dyn UiElement>> {
+    if ui_element.get_id() == id {
+        return Some(ui_element);
+    }
+    ui_element.get_children_mut().into_iter().find_map(|child| find_in(child, id))
+}
+
+fn remove_in(ui_element: &mut Box<dyn UiElement>, id: u64) -> Option<Box<dyn UiElement>> {
+    if let Some(child) = ui_element.remove_child(id) {
+        return Some(child);
+    }
+    ui_element.get_children_mut().into_iter().find_map(|child| remove_in(child, id))
+}
+
+fn collect_ids(ui_element: &mut Box<dyn UiElement>, ids: &mut Vec<u64>) {
+    ids.push(ui_element.get_id());
+    for child in ui_element.get_children_mut() {
+        collect_ids(child, ids);
+    }
+}
+
+// deepest hovered element with a tooltip, the hovered part of an element comes before the element itself
+fn find_tooltip(ui_element: &mut Box<dyn UiElement>, app: &Application, grid: &Grid, tooltips: &HashMap<u64, String>) -> Option<String> {
+    if !ui_element.is_mouse_on_element(app, grid) {
+        return None;
+    }
+    for child in ui_element.get_shown_children_mut() {
+        if let Some(tooltip) = find_tooltip(child, app, grid, tooltips) {
+            return Some(tooltip);
         }
     }
+    ui_element.get_tooltip().or_else(|| tooltips.get(&ui_element.get_id())).cloned()
+}
+
+fn tree_roots() -> Vec<UiTreeNode> {
+    vec![UiTreeNode::new_lazy(String::from("src"))]
+}
+
+fn find_text_id(ui_element: &mut Box<dyn UiElement>) -> Option<u64> {
+    if ui_element.as_any().is::<UiText>() {
+        return Some(ui_element.get_id());
+    }
+    ui_element.get_children_mut().into_iter().find_map(find_text_id)
+}
+
+fn update_delta_time(ui_text: &mut UiText, app: &Application, _grid: &Grid) -> Result<(), UiError> {
+    ui_text.set_text(format!("{} ms", app.delta_time))
+}
+
+fn update_mouse_position(ui_text: &mut UiText, app: &Application, _grid: &Grid) -> Result<(), UiError> {
+    ui_text.set_text(format!("Mouse coordinate: {}, {}", app.cursor_position.x, app.cursor_position.y))
+}
+
+fn update_grid_position(ui_text: &mut UiText, app: &Application, grid: &Grid) -> Result<(), UiError> {
+    let grid_pos_x = (app.cursor_position.x / app.width as f64 * grid.width as f64).floor() as i32;
+    let grid_pos_y = (app.cursor_position.y / app.height as f64 * grid.height as f64).floor() as i32;
+    ui_text.set_text(format!("Grid coordinate: {grid_pos_x}, {grid_pos_y}"))
 }
\ No newline at end of file
@@ -0,0 +1,375 @@
+// screens described in a toml file, built by Screen::load_file:
+// [colors] sets the named colors of the grid, name = "RRGGBB"
+// [[elements]] lists the elements, type is "text", "list", "panel" or "split", the other fields depend on it
+// [layout] places the elements by name, children can be layouts or elements
+// closures cannot be written in a file, update functions and callbacks are referenced by name from a ScreenRegistry
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::layout::{Alignment, Constraint, Direction, Layout, Spacing};
+use crate::interface::scene_manager::Transition;
+use crate::interface::theme::{Theme, Widget};
+use crate::interface::ui_action::{Callback, UiAction};
+use crate::interface::ui_console::LogLevel;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::ui_list::{UiList, UiListItem};
+use crate::interface::ui_panel::UiPanel;
+use crate::interface::ui_split::UiSplit;
+use crate::interface::ui_text::UiText;
+use crate::interface::ui_viewport::UiViewport;
+use crate::interface::word::{TextAlign, VerticalAlign};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+pub type TextUpdateFunction = Box<dyn FnMut(&mut UiText, &Application, &Grid) -> Result<(), UiError>>;
+
+// functions the screen files can reference by name, the texts using the same update function share it
+#[derive(Default)]
+pub struct ScreenRegistry {
+    pub update_functions: HashMap<String, Rc<RefCell<TextUpdateFunction>>>,
+    pub callbacks: HashMap<String, Callback>,
+}
+
+impl ScreenRegistry {
+    pub fn new() -> ScreenRegistry {
+        ScreenRegistry::default()
+    }
+
+    pub fn add_update_function(&mut self, name: &str, update_function: impl FnMut(&mut UiText, &Application, &Grid) -> Result<(), UiError> + 'static) {
+        let update_function: TextUpdateFunction = Box::new(update_function);
+        self.update_functions.insert(String::from(name), Rc::new(RefCell::new(update_function)));
+    }
+
+    pub fn add_callback(&mut self, name: &str, callback: Callback) {
+        self.callbacks.insert(String::from(name), callback);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScreenFile {
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+    #[serde(default)]
+    pub elements: Vec<ElementDefinition>,
+    pub layout: Option<LayoutDefinition>,
+}
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElementType {
+    Text,
+    List,
+    Panel,
+    Split,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ElementDefinition {
+    // used by the layout and the actions to find the element, the ratio of a named split is saved
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub element_type: ElementType,
+    // top left corner, bottom left corner relative to the content for the children of a panel
+    #[serde(default)]
+    pub pos: [i32; 2],
+    #[serde(default)]
+    pub size: [i32; 2],
+    pub fg_color: Option<String>,
+    pub bg_color: Option<String>,
+    #[serde(rename = "box")]
+    pub box_type: Option<BoxDrawing>,
+    // the element is scrolled inside a viewport
+    #[serde(default)]
+    pub viewport: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    // shown when the element is hovered
+    pub tooltip: Option<String>,
+    // text
+    pub text: Option<String>,
+    // markup file shown instead of the text and reloaded when it changes
+    pub file: Option<String>,
+    pub wrap: Option<bool>,
+    pub align: Option<TextAlign>,
+    pub vertical_align: Option<VerticalAlign>,
+    pub update: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<ActionDefinition>,
+    // texts of the `t tags
+    #[serde(default)]
+    pub tooltips: Vec<String>,
+    // list
+    #[serde(default)]
+    pub items: Vec<ListItemDefinition>,
+    // panel, or the two children of a split
+    #[serde(default)]
+    pub children: Vec<ElementDefinition>,
+    // split
+    pub direction: Option<Direction>,
+    pub ratio: Option<f32>,
+    // cells kept for the first and the second child
+    pub min_sizes: Option<[i32; 2]>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListItemDefinition {
+    pub text: String,
+    pub action: Option<ActionDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionDefinition {
+    AddText { text: String, pos: [i32; 2], size: [i32; 2] },
+    ShowDialog { title: String, body: String, buttons: Vec<String> },
+    // elements are found by name, only the ones built before are known
+    SetVisible { element: String, visible: bool },
+    RemoveElement { element: String },
+    SetText { element: String, text: String },
+    Message { text: String },
+    Callback { name: String },
+    // scenes are found by name in the scene manager, without transition by default
+    PushScene { scene: String, transition: Option<Transition> },
+    PopScene { transition: Option<Transition> },
+    ReplaceScene { scene: String, transition: Option<Transition> },
+    // duration in ms, the default one of the screen when missing
+    Toast { level: LogLevel, text: String, duration: Option<u64> },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutDefinition {
+    pub direction: Option<Direction>,
+    pub size: Option<Constraint>,
+    pub cross_size: Option<Constraint>,
+    pub padding: Option<SpacingDefinition>,
+    pub margin: Option<SpacingDefinition>,
+    pub align: Option<Alignment>,
+    pub justify: Option<Alignment>,
+    pub element: Option<String>,
+    #[serde(default)]
+    pub children: Vec<LayoutDefinition>,
+}
+
+// the same value on all sides or left, right, top and bottom
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum SpacingDefinition {
+    All(i32),
+    Sides([i32; 4]),
+}
+
+impl SpacingDefinition {
+    fn to_spacing(&self) -> Spacing {
+        match self {
+            SpacingDefinition::All(value) => Spacing::all(*value),
+            SpacingDefinition::Sides([left, right, top, bottom]) => Spacing::new(*left, *right, *top, *bottom),
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Result<ScreenFile, UiError> {
+    Ok(toml::from_str(text)?)
+}
+
+pub fn parse_color(color: &String) -> Result<RGBA8, UiError> {
+    // the same form as the markup, longer or shorter strings would be cut or read partially
+    if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(UiError::Parse(format!("Invalid color \"{color}\": expected 6 hexadecimal digits")));
+    }
+    RGBA8::from_hex_string(color).map_err(|error| UiError::Parse(format!("Invalid color \"{color}\": {error}")))
+}
+
+// builds the elements of a file, the names map to the ids given by the screen
+// the colors of the file are applied over the ones of the theme
+pub struct ElementBuilder<'a> {
+    pub registry: &'a ScreenRegistry,
+    pub theme: &'a Theme,
+    pub names: &'a HashMap<String, u64>,
+}
+
+impl ElementBuilder<'_> {
+    pub fn build(&self, definition: &ElementDefinition) -> Result<Box<dyn UiElement>, UiError> {
+        let pos = Vector2::new(definition.pos[0], definition.pos[1]);
+        let size = Vector2::new(definition.size[0], definition.size[1]);
+        let element: Box<dyn UiElement> = match definition.element_type {
+            ElementType::Text => Box::new(self.build_text(definition, pos, size)?),
+            ElementType::List => {
+                let mut items = Vec::new();
+                for item in definition.items.iter() {
+                    let mut list_item = UiListItem::new(item.text.clone());
+                    if let Some(action) = &item.action {
+                        list_item.action = Some(self.build_action(action)?);
+                    }
+                    items.push(list_item);
+                }
+                let mut list = UiList::new(items, pos, size);
+                list.set_theme(self.theme);
+                if let Some(color) = &definition.fg_color {
+                    list.fg_color = parse_color(color)?;
+                }
+                if let Some(color) = &definition.bg_color {
+                    list.bg_color = parse_color(color)?;
+                }
+                Box::new(list)
+            }
+            ElementType::Panel => {
+                let mut panel = UiPanel::new(pos, size);
+                // only the panel, set_theme would also restyle the children built with their own colors
+                let style = self.theme.get_style(Widget::Panel);
+                panel.fg_color = style.normal.fg_color;
+                panel.bg_color = style.normal.bg_color;
+                if let Some(color) = &definition.fg_color {
+                    panel.fg_color = parse_color(color)?;
+                }
+                if let Some(color) = &definition.bg_color {
+                    panel.bg_color = parse_color(color)?;
+                }
+                if let Some(box_type) = definition.box_type {
+                    panel.set_box_drawing(true, box_type)?;
+                }
+                for child in definition.children.iter() {
+                    let child_pos = Vector2::new(child.pos[0], child.pos[1]);
+                    let child_size = Vector2::new(child.size[0], child.size[1]);
+                    panel.add_child(self.build(child)?, child_pos, child_size)?;
+                }
+                Box::new(panel)
+            }
+            ElementType::Split => {
+                let mut split = self.build_split(definition)?;
+                // the position of the file is the top left corner, the bounds start at the bottom left one
+                split.set_bounds(Vector2::new(pos.x, pos.y - size.y + 1), size)?;
+                Box::new(split)
+            }
+        };
+        if definition.viewport {
+            return Ok(Box::new(UiViewport::new(element)));
+        }
+        Ok(element)
+    }
+
+    fn build_split(&self, definition: &ElementDefinition) -> Result<UiSplit, UiError> {
+        let (first, second) = match definition.children.as_slice() {
+            [first, second] => (self.build(first)?, self.build(second)?),
+            children => return Err(UiError::Parse(format!("A split needs two children, found {}", children.len()))),
+        };
+        let mut split = UiSplit::new(definition.direction.unwrap_or(Direction::Row), first, second, definition.ratio.unwrap_or(0.5));
+        // only the divider, set_theme would also restyle the children built with their own colors
+        let style = self.theme.get_style(Widget::Split);
+        split.fg_color = style.normal.fg_color;
+        split.bg_color = style.normal.bg_color;
+        split.hover_colors = Some(style.hover);
+        split.box_type = style.box_type;
+        if let Some(color) = &definition.fg_color {
+            split.fg_color = parse_color(color)?;
+        }
+        if let Some(color) = &definition.bg_color {
+            split.bg_color = parse_color(color)?;
+        }
+        if let Some(box_type) = definition.box_type {
+            split.box_type = box_type;
+        }
+        if let Some([min_first, min_second]) = definition.min_sizes {
+            split.min_first = min_first;
+            split.min_second = min_second;
+        }
+        Ok(split)
+    }
+
+    fn build_text(&self, definition: &ElementDefinition, pos: Vector2, size: Vector2) -> Result<UiText, UiError> {
+        let text = definition.text.clone().unwrap_or_default();
+        let mut ui_text = UiText::new(text, pos, size)?;
+        ui_text.set_theme(self.theme);
+        if let Some(color) = &definition.fg_color {
+            ui_text.fg_color = parse_color(color)?;
+        }
+        if let Some(color) = &definition.bg_color {
+            ui_text.bg_color = parse_color(color)?;
+        }
+        if let Some(box_type) = definition.box_type {
+            ui_text.set_box_drawing(true, box_type);
+        }
+        if let Some(wrap) = definition.wrap {
+            ui_text.set_wrap(wrap)?;
+        }
+        if definition.align.is_some() || definition.vertical_align.is_some() {
+            ui_text.set_alignment(definition.align.unwrap_or(TextAlign::Left), definition.vertical_align.unwrap_or(VerticalAlign::Top))?;
+        }
+        if let Some(name) = &definition.update {
+            match self.registry.update_functions.get(name) {
+                Some(update_function) => {
+                    let update_function = update_function.clone();
+                    ui_text.set_update_function(move |ui_text: &mut UiText, app: &Application, grid: &Grid| (update_function.borrow_mut())(ui_text, app, grid));
+                }
+                None => return Err(UiError::NotFound(format!("No update function named {name} in the registry"))),
+            }
+        }
+        for action in definition.actions.iter() {
+            ui_text.actions.push(self.build_action(action)?);
+        }
+        ui_text.tooltips = definition.tooltips.clone();
+        Ok(ui_text)
+    }
+
+    fn build_action(&self, definition: &ActionDefinition) -> Result<UiAction, UiError> {
+        let action = match definition {
+            ActionDefinition::AddText { text, pos, size } => {
+                UiAction::AddUiText(text.clone(), Vector2::new(pos[0], pos[1]), Vector2::new(size[0], size[1]))
+            }
+            ActionDefinition::ShowDialog { title, body, buttons } => UiAction::ShowDialog(title.clone(), body.clone(), buttons.clone()),
+            ActionDefinition::SetVisible { element, visible } => UiAction::SetVisible(self.get_id(element)?, *visible),
+            ActionDefinition::RemoveElement { element } => UiAction::RemoveElement(self.get_id(element)?),
+            ActionDefinition::SetText { element, text } => UiAction::SetText(self.get_id(element)?, text.clone()),
+            ActionDefinition::Message { text } => UiAction::message(text.clone()),
+            ActionDefinition::Callback { name } => match self.registry.callbacks.get(name) {
+                Some(callback) => UiAction::Callback(callback.clone()),
+                None => return Err(UiError::NotFound(format!("No callback named {name} in the registry"))),
+            },
+            ActionDefinition::PushScene { scene, transition } => UiAction::PushScene(scene.clone(), transition.unwrap_or(Transition::Cut)),
+            ActionDefinition::PopScene { transition } => UiAction::PopScene(transition.unwrap_or(Transition::Cut)),
+            ActionDefinition::ReplaceScene { scene, transition } => UiAction::ReplaceScene(scene.clone(), transition.unwrap_or(Transition::Cut)),
+            ActionDefinition::Toast { level, text, duration } => UiAction::Toast(*level, text.clone(), duration.map(Duration::from_millis)),
+        };
+        Ok(action)
+    }
+
+    pub fn build_layout(&self, definition: &LayoutDefinition) -> Result<Layout, UiError> {
+        let mut children = Vec::new();
+        for child in definition.children.iter() {
+            children.push(self.build_layout(child)?);
+        }
+        let direction = definition.direction.unwrap_or(Direction::Column);
+        let mut layout = Layout::new(direction, definition.size.unwrap_or(Constraint::Fill(1)), children);
+        if let Some(cross_size) = definition.cross_size {
+            layout.cross_constraint = cross_size;
+        }
+        if let Some(padding) = &definition.padding {
+            layout.padding = padding.to_spacing();
+        }
+        if let Some(margin) = &definition.margin {
+            layout.margin = margin.to_spacing();
+        }
+        if let Some(align) = definition.align {
+            layout.align = align;
+        }
+        if let Some(justify) = definition.justify {
+            layout.justify = justify;
+        }
+        if let Some(name) = &definition.element {
+            layout.element = Some(self.get_id(name)?);
+        }
+        Ok(layout)
+    }
+
+    fn get_id(&self, name: &str) -> Result<u64, UiError> {
+        self.names.get(name).copied().ok_or_else(|| UiError::NotFound(format!("No element named {name}")))
+    }
+}
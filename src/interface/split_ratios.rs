@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::interface::ui_error::UiError;
+
+#[derive(Default, Serialize, Deserialize)]
+struct RatiosFile {
+    #[serde(default)]
+    ratios: BTreeMap<String, f32>,
+}
+
+// ratios of the named splits, kept in a toml file so the layouts survive restarts
+pub struct SplitRatios {
+    // none until a file is loaded, the ratios are then only kept in memory
+    path: Option<PathBuf>,
+    ratios: BTreeMap<String, f32>,
+}
+
+impl SplitRatios {
+    pub fn new() -> SplitRatios {
+        SplitRatios {
+            path: None,
+            ratios: BTreeMap::new(),
+        }
+    }
+
+    // a missing file is created by the next save
+    pub fn load(&mut self, path: PathBuf) -> Result<(), UiError> {
+        if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            let file: RatiosFile = toml::from_str(&text).map_err(|error| UiError::Parse(format!("{}: {error}", path.display())))?;
+            self.ratios = file.ratios;
+        }
+        self.path = Some(path);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.ratios.get(name).copied()
+    }
+
+    // save the file with the new ratio
+    pub fn set(&mut self, name: String, ratio: f32) -> Result<(), UiError> {
+        self.ratios.insert(name, ratio);
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let file = RatiosFile { ratios: self.ratios.clone() };
+        let text = toml::to_string(&file).map_err(|error| UiError::Error(format!("Failed to write the split ratios: {error}")))?;
+        fs::write(path, text).map_err(|error| UiError::Io(format!("{}: {error}", path.display())))
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use crate::Grid;
+use crate::interface::box_drawing::BoxDrawing;
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+#[derive(Copy, Clone)]
+pub struct StateColors {
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+}
+
+impl StateColors {
+    pub fn new(fg_color: RGBA8, bg_color: RGBA8) -> StateColors {
+        StateColors { fg_color, bg_color }
+    }
+
+    // paint the area with the colors, or invert it when there are none
+    pub fn paint(colors: Option<StateColors>, grid: &mut Grid, start: Vector2, end: Vector2) {
+        match colors {
+            Some(colors) => {
+                grid.set_fg_from_to(start, end, colors.fg_color.into());
+                grid.set_bg_from_to(start, end, colors.bg_color.into());
+            }
+            None => grid.inverse_color_from_to(start, end),
+        }
+    }
+}
+
+// colors of a type of element in each of its states
+#[derive(Copy, Clone)]
+pub struct WidgetStyle {
+    pub normal: StateColors,
+    // under the mouse
+    pub hover: StateColors,
+    // cursor or button receiving the keys
+    pub focus: StateColors,
+    // for the elements which can be disabled
+    pub disabled: StateColors,
+    pub box_type: BoxDrawing,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Widget {
+    Text,
+    List,
+    Table,
+    Tree,
+    Tabs,
+    Dialog,
+    Panel,
+    Tooltip,
+    Window,
+    Toast,
+    Console,
+    Split,
+}
+
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    // colors of the cells no element draws on
+    pub background: StateColors,
+    // named colors of the grid used by the markup, like `c{warning}
+    pub colors: HashMap<String, RGBA8>,
+    pub styles: HashMap<Widget, WidgetStyle>,
+}
+
+impl Theme {
+    pub fn new(name: String, background: StateColors) -> Theme {
+        Theme {
+            name,
+            background,
+            colors: HashMap::new(),
+            styles: HashMap::new(),
+        }
+    }
+
+    pub fn dark() -> Theme {
+        let white = RGBA8::new(255, 255, 255, 255);
+        let black = RGBA8::new(0, 0, 0, 255);
+        let mut theme = Theme::new(String::from("dark"), StateColors::new(white, black));
+        theme.set_colors(&[
+            ("info", RGBA8::new(0, 170, 255, 255)),
+            ("success", RGBA8::new(0, 200, 80, 255)),
+            ("warning", RGBA8::new(255, 170, 0, 255)),
+            ("error", RGBA8::new(255, 50, 50, 255)),
+            ("selection", RGBA8::new(0, 95, 175, 255)),
+            ("header_fg", black),
+            ("header_bg", RGBA8::new(200, 200, 200, 255)),
+        ]);
+        let style = WidgetStyle {
+            normal: StateColors::new(white, black),
+            hover: StateColors::new(black, white),
+            focus: StateColors::new(black, white),
+            disabled: StateColors::new(RGBA8::new(110, 110, 110, 255), black),
+            box_type: BoxDrawing::Light,
+        };
+        theme.set_all_styles(style);
+        theme.styles.insert(Widget::Dialog, WidgetStyle { box_type: BoxDrawing::Double, ..style });
+        theme.styles.insert(Widget::Tooltip, WidgetStyle { normal: StateColors::new(white, RGBA8::new(45, 45, 55, 255)), box_type: BoxDrawing::Arc, ..style });
+        theme.styles.insert(Widget::Toast, WidgetStyle { normal: StateColors::new(white, RGBA8::new(30, 30, 40, 255)), box_type: BoxDrawing::Arc, ..style });
+        theme.styles.insert(Widget::Console, WidgetStyle { normal: StateColors::new(RGBA8::new(210, 210, 210, 255), RGBA8::new(20, 20, 25, 255)), ..style });
+        theme
+    }
+
+    pub fn light() -> Theme {
+        let white = RGBA8::new(250, 250, 245, 255);
+        let black = RGBA8::new(30, 30, 30, 255);
+        let mut theme = Theme::new(String::from("light"), StateColors::new(black, white));
+        theme.set_colors(&[
+            ("info", RGBA8::new(0, 90, 180, 255)),
+            ("success", RGBA8::new(0, 130, 50, 255)),
+            ("warning", RGBA8::new(180, 100, 0, 255)),
+            ("error", RGBA8::new(200, 0, 0, 255)),
+            ("selection", RGBA8::new(170, 205, 240, 255)),
+            ("header_fg", white),
+            ("header_bg", RGBA8::new(80, 80, 90, 255)),
+        ]);
+        let style = WidgetStyle {
+            normal: StateColors::new(black, white),
+            hover: StateColors::new(black, RGBA8::new(220, 225, 235, 255)),
+            focus: StateColors::new(white, RGBA8::new(40, 100, 180, 255)),
+            disabled: StateColors::new(RGBA8::new(160, 160, 160, 255), white),
+            box_type: BoxDrawing::Light,
+        };
+        theme.set_all_styles(style);
+        theme.styles.insert(Widget::Dialog, WidgetStyle { box_type: BoxDrawing::Double, ..style });
+        theme.styles.insert(Widget::Tooltip, WidgetStyle { normal: StateColors::new(black, RGBA8::new(255, 250, 215, 255)), box_type: BoxDrawing::Arc, ..style });
+        theme.styles.insert(Widget::Toast, WidgetStyle { normal: StateColors::new(black, RGBA8::new(235, 235, 240, 255)), box_type: BoxDrawing::Arc, ..style });
+        theme.styles.insert(Widget::Console, WidgetStyle { normal: StateColors::new(black, RGBA8::new(240, 240, 235, 255)), ..style });
+        theme
+    }
+
+    // only pure colors and heavy boxes
+    pub fn high_contrast() -> Theme {
+        let white = RGBA8::new(255, 255, 255, 255);
+        let black = RGBA8::new(0, 0, 0, 255);
+        let yellow = RGBA8::new(255, 255, 0, 255);
+        let mut theme = Theme::new(String::from("high contrast"), StateColors::new(white, black));
+        theme.set_colors(&[
+            ("info", RGBA8::new(0, 255, 255, 255)),
+            ("success", RGBA8::new(0, 255, 0, 255)),
+            ("warning", yellow),
+            ("error", RGBA8::new(255, 0, 0, 255)),
+            ("selection", RGBA8::new(0, 0, 255, 255)),
+            ("header_fg", black),
+            ("header_bg", yellow),
+        ]);
+        let style = WidgetStyle {
+            normal: StateColors::new(white, black),
+            hover: StateColors::new(black, yellow),
+            focus: StateColors::new(black, RGBA8::new(0, 255, 255, 255)),
+            disabled: StateColors::new(RGBA8::new(0, 255, 0, 255), black),
+            box_type: BoxDrawing::Heavy,
+        };
+        theme.set_all_styles(style);
+        theme
+    }
+
+    fn set_colors(&mut self, colors: &[(&str, RGBA8)]) {
+        for (name, color) in colors {
+            self.colors.insert(String::from(*name), *color);
+        }
+    }
+
+    fn set_all_styles(&mut self, style: WidgetStyle) {
+        for widget in [Widget::Text, Widget::List, Widget::Table, Widget::Tree, Widget::Tabs, Widget::Dialog, Widget::Panel, Widget::Tooltip, Widget::Window, Widget::Toast, Widget::Console, Widget::Split] {
+            self.styles.insert(widget, style);
+        }
+    }
+
+    // the background colors are used for the widgets without a style
+    pub fn get_style(&self, widget: Widget) -> WidgetStyle {
+        match self.styles.get(&widget) {
+            Some(style) => *style,
+            None => WidgetStyle {
+                normal: self.background,
+                hover: self.background,
+                focus: self.background,
+                disabled: self.background,
+                box_type: BoxDrawing::Light,
+            },
+        }
+    }
+
+    // missing colors fall back to the foreground
+    pub fn get_color(&self, name: &str) -> RGBA8 {
+        self.colors.get(name).copied().unwrap_or(self.background.fg_color)
+    }
+}
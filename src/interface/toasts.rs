@@ -0,0 +1,195 @@
+use std::time::Duration;
+use serde::Deserialize;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::theme::{Theme, Widget};
+use crate::interface::ui_console::LogLevel;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::ui_text::UiText;
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// wide enough for the level on the top border
+const MIN_WIDTH: i32 = 12;
+
+// corner of the grid the toasts are stacked from
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+struct Toast {
+    level: LogLevel,
+    popup: UiText,
+    // bottom left corner and size with the border
+    pos: Vector2,
+    size: Vector2,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl Toast {
+    fn contains(&self, cell: Vector2) -> bool {
+        cell.x >= self.pos.x && cell.x < self.pos.x + self.size.x &&
+        cell.y >= self.pos.y && cell.y < self.pos.y + self.size.y
+    }
+
+    // 1 until the last part of the duration, then down to 0 when the toast expires
+    fn opacity(&self, fade: Duration) -> f32 {
+        let remaining = self.duration.saturating_sub(self.elapsed);
+        if fade.is_zero() || remaining >= fade {
+            return 1.0;
+        }
+        remaining.as_secs_f32() / fade.as_secs_f32()
+    }
+}
+
+// short messages stacked in a corner of the grid, drawn by the screen over the elements
+// a toast expires after its duration, fading out at the end, or once clicked
+pub struct Toasts {
+    pub corner: ToastCorner,
+    // used by the toasts shown without a duration
+    pub duration: Duration,
+    // end of the duration over which the toasts fade out, zero to make them disappear at once
+    pub fade: Duration,
+    // the longer lines are wrapped
+    pub max_width: i32,
+    // the oldest ones are removed over it
+    pub max_toasts: usize,
+    // cells between the toasts and the edges of the grid
+    pub margin: i32,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+    // oldest first, the newest one is the closest to the corner
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn new() -> Toasts {
+        Toasts {
+            corner: ToastCorner::TopRight,
+            duration: Duration::from_secs(4),
+            fade: Duration::from_millis(500),
+            max_width: 36,
+            max_toasts: 5,
+            margin: 1,
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Arc,
+            toasts: Vec::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Toast);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.box_type = style.box_type;
+        for toast in self.toasts.iter_mut() {
+            toast.popup.fg_color = self.fg_color;
+            toast.popup.bg_color = self.bg_color;
+            toast.popup.set_box_drawing(true, self.box_type);
+        }
+    }
+
+    // text is markup
+    pub fn show(&mut self, level: LogLevel, text: String, duration: Option<Duration>, grid: &Grid) -> Result<(), UiError> {
+        let grid_width = grid.width as i32;
+        let max_width = self.max_width.min(grid_width - 2 - 2 * self.margin).max(1);
+        let mut popup = UiText::new(text, Vector2::new(0, 0), Vector2::new(max_width, 1))?;
+        popup.fg_color = self.fg_color;
+        popup.bg_color = self.bg_color;
+        popup.highlight_word = false;
+        popup.set_box_drawing(true, self.box_type);
+        let size = Vector2::new((popup.get_content_width() + 2).max(MIN_WIDTH), popup.get_max_size().y + 2);
+
+        let pos = Vector2::new(0, 0);
+        self.toasts.push(Toast { level, popup, pos, size, duration: duration.unwrap_or(self.duration), elapsed: Duration::ZERO });
+        if self.toasts.len() > self.max_toasts.max(1) {
+            self.toasts.remove(0);
+        }
+        self.place(grid)
+    }
+
+    pub fn is_mouse_on(&self, app: &Application) -> bool {
+        self.toasts.iter().any(|toast| toast.contains(app.grid_position))
+    }
+
+    // the hovered toast does not expire, a click dismisses it
+    pub fn update(&mut self, app: &Application, grid: &Grid) -> Result<(), UiError> {
+        let count = self.toasts.len();
+        self.toasts.retain_mut(|toast| {
+            if toast.contains(app.grid_position) {
+                return app.mouse_left != 1;
+            }
+            toast.elapsed += app.frame_time;
+            toast.elapsed < toast.duration
+        });
+        if self.toasts.len() != count {
+            self.place(grid)?;
+        }
+        Ok(())
+    }
+
+    // stacked from the corner, the ones out of the grid are placed anyway and clipped by it
+    pub fn place(&mut self, grid: &Grid) -> Result<(), UiError> {
+        let grid_size = Vector2::new(grid.width as i32, grid.height as i32);
+        let (left, top) = match self.corner {
+            ToastCorner::TopLeft => (true, true),
+            ToastCorner::TopRight => (false, true),
+            ToastCorner::BottomLeft => (true, false),
+            ToastCorner::BottomRight => (false, false),
+        };
+        // next free row from the corner
+        let mut y = if top { grid_size.y - self.margin } else { self.margin };
+        for toast in self.toasts.iter_mut().rev() {
+            let size = toast.size;
+            let x = if left { self.margin } else { grid_size.x - self.margin - size.x };
+            let pos = if top {
+                y -= size.y;
+                Vector2::new(x, y)
+            } else {
+                y += size.y;
+                Vector2::new(x, y - size.y)
+            };
+            toast.pos = pos;
+            toast.popup.set_bounds(pos, size)?;
+        }
+        Ok(())
+    }
+
+    // the border and the level take the named color of the level
+    pub fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        if self.toasts.is_empty() {
+            return Ok(());
+        }
+        let fading = self.toasts.iter().any(|toast| toast.opacity(self.fade) < 1.0);
+        let below = if fading { grid.get_quads().to_vec() } else { Vec::new() };
+        for toast in self.toasts.iter() {
+            toast.popup.draw(grid)?;
+            let start = toast.pos;
+            let end = start + toast.size;
+            let color: [f32; 4] = grid.get_named_color(toast.level.get_name()).unwrap_or(self.fg_color).into();
+            grid.set_fg_from_to(start, Vector2::new(end.x, start.y + 1), color);
+            grid.set_fg_from_to(Vector2::new(start.x, end.y - 1), end, color);
+            grid.set_fg_from_to(start, Vector2::new(start.x + 1, end.y), color);
+            grid.set_fg_from_to(Vector2::new(end.x - 1, start.y), end, color);
+            grid.write_at(Vector2::new(start.x + 2, end.y - 1), &format!(" {} ", toast.level.get_name()));
+        }
+        if fading {
+            grid.blend_from(&below, |cell| {
+                self.toasts.iter().rev()
+                    .find(|toast| toast.contains(cell))
+                    .map(|toast| toast.opacity(self.fade))
+                    .unwrap_or(1.0)
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::theme::{Theme, Widget};
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::ui_text::UiText;
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// boxed popup with the tooltip under the cursor, drawn by the screen over everything else
+pub struct Tooltip {
+    // time in ms the cursor has to stay still on the tooltip before it appears
+    pub delay: u128,
+    // the longer lines are wrapped
+    pub max_width: i32,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+    // markup of the hovered tooltip
+    text: Option<String>,
+    cursor: Vector2,
+    elapsed: u128,
+    // built once the delay is over, stays in place until the tooltip changes
+    popup: Option<UiText>,
+}
+
+impl Tooltip {
+    pub fn new() -> Tooltip {
+        Tooltip {
+            delay: 600,
+            max_width: 40,
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Arc,
+            text: None,
+            cursor: Vector2::new(0, 0),
+            elapsed: 0,
+            popup: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Tooltip);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.box_type = style.box_type;
+        self.popup = None;
+        self.elapsed = 0;
+    }
+
+    // text is the tooltip under the cursor, moving the cursor before the popup appears starts the delay again
+    pub fn update(&mut self, text: Option<&String>, app: &Application, grid: &Grid) -> Result<(), UiError> {
+        if text != self.text.as_ref() {
+            self.text = text.cloned();
+            self.cursor = app.grid_position;
+            self.elapsed = 0;
+            self.popup = None;
+            return Ok(());
+        }
+        if self.popup.is_some() {
+            return Ok(());
+        }
+        if app.grid_position != self.cursor {
+            self.cursor = app.grid_position;
+            self.elapsed = 0;
+            return Ok(());
+        }
+        self.elapsed += app.delta_time;
+        if self.elapsed >= self.delay {
+            if let Some(text) = &self.text {
+                self.popup = Some(self.build_popup(text, grid)?);
+            }
+        }
+        Ok(())
+    }
+
+    // below and right of the cursor, above it when there is no room, kept inside the grid
+    fn build_popup(&self, text: &str, grid: &Grid) -> Result<UiText, UiError> {
+        let grid_size = Vector2::new(grid.width as i32, grid.height as i32);
+        let max_width = self.max_width.min(grid_size.x - 2).max(1);
+        let mut popup = UiText::new(String::from(text), Vector2::new(0, 0), Vector2::new(max_width, 1))?;
+        popup.fg_color = self.fg_color;
+        popup.bg_color = self.bg_color;
+        popup.highlight_word = false;
+        popup.set_box_drawing(true, self.box_type);
+        let size = Vector2::new(popup.get_content_width().max(1) + 2, popup.get_max_size().y + 2);
+        let mut pos = Vector2::new(self.cursor.x + 1, self.cursor.y - size.y);
+        if pos.y < 0 {
+            pos.y = self.cursor.y + 1;
+        }
+        pos.x = pos.x.clamp(0, (grid_size.x - size.x).max(0));
+        pos.y = pos.y.clamp(0, (grid_size.y - size.y).max(0));
+        popup.set_bounds(pos, size)?;
+        Ok(popup)
+    }
+
+    pub fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        match &self.popup {
+            Some(popup) => popup.draw(grid),
+            None => Ok(()),
+        }
+    }
+}
@@ -1,8 +1,59 @@
+use std::any::Any;
+use std::rc::Rc;
+use std::time::Duration;
+use crate::interface::scene_manager::Transition;
+use crate::interface::screen::Screen;
+use crate::interface::ui_console::LogLevel;
 use crate::interface::ui_error::UiError;
 use crate::util::vector2::{Vector2};
 
+pub type Callback = Rc<dyn Fn(&mut Screen) -> Result<(), UiError>>;
+
 #[derive(Clone)]
 pub enum UiAction {
     AddUiText(String, Vector2, Vector2),
+    // logged in the console of the screen, with the name of the element for the element errors
     WriteError(UiError),
-}
\ No newline at end of file
+    // message logged in the console of the screen
+    Log(LogLevel, String),
+    // level, markup, shown for the default duration of the screen toasts when none
+    Toast(LogLevel, String, Option<Duration>),
+    // element id, selected indices (node path for trees)
+    SelectionChanged(u64, Vec<usize>),
+    // element id, activated index (node path for trees)
+    Activated(u64, Vec<usize>),
+    // title, body, buttons
+    ShowDialog(String, String, Vec<String>),
+    // dialog id, pressed button or none when dismissed with escape
+    DialogClosed(u64, Option<usize>),
+    // element id
+    RemoveElement(u64),
+    // element id, visible, only for the elements added to the screen
+    SetVisible(u64, bool),
+    // element id, new index in the update and draw order of the screen
+    MoveElement(u64, usize),
+    // id of a UiSplit, ratio once its divider is released, saved for the named splits
+    SplitMoved(u64, f32),
+    // id of a UiText, new text
+    SetText(u64, String),
+    // application code run with the screen
+    Callback(Callback),
+    // application defined message, given to the event bus subscribers of its type
+    Message(Rc<dyn Any>),
+    // name of a scene registered in the scene manager, put over the current one
+    PushScene(String, Transition),
+    // back to the scene below, the application ends with the last one
+    PopScene(Transition),
+    // name of a scene registered in the scene manager, put in place of the current one
+    ReplaceScene(String, Transition),
+}
+
+impl UiAction {
+    pub fn callback(callback: impl Fn(&mut Screen) -> Result<(), UiError> + 'static) -> UiAction {
+        UiAction::Callback(Rc::new(callback))
+    }
+
+    pub fn message<T: 'static>(message: T) -> UiAction {
+        UiAction::Message(Rc::new(message))
+    }
+}
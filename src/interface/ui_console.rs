@@ -0,0 +1,303 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use glfw::Key;
+use serde::Deserialize;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+const CLEAR_BUTTON: &str = "[clear]";
+const CLOSE_BUTTON: &str = "[x]";
+// entries compared with a new one to count it as a repetition
+const DEDUPE_RANGE: usize = 20;
+
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Info,
+    #[serde(alias = "warn")]
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    // label of the messages, also the named color of the grid used for it
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+pub struct LogEntry {
+    pub level: LogLevel,
+    // level or kind of error
+    pub label: String,
+    // name or id of the element it comes from
+    pub source: Option<String>,
+    pub message: String,
+    // times it was logged, the repetitions do not add lines
+    pub count: u32,
+    // ms since the console was created, updated by the repetitions
+    pub time: u128,
+}
+
+// log of the errors and messages of a screen, docked at the bottom of the grid
+// F12 shows or hides it, it opens by itself on new errors
+pub struct UiConsole {
+    id: u64,
+    entries: VecDeque<LogEntry>,
+    // oldest entries are removed over it
+    pub max_entries: usize,
+    // new entries per second, the other ones are counted and reported once the second is over
+    pub rate_limit: usize,
+    window_start: u128,
+    window_count: usize,
+    dropped: usize,
+    elapsed: u128,
+    // lines scrolled up from the newest entry
+    offset: i32,
+    // rows taken with the border
+    pub height: i32,
+    pub open_on_error: bool,
+    open: bool,
+    pos: Vector2,
+    size: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+}
+
+impl UiConsole {
+    pub fn new() -> UiConsole {
+        UiConsole {
+            id: 0,
+            entries: VecDeque::new(),
+            max_entries: 500,
+            rate_limit: 10,
+            window_start: 0,
+            window_count: 0,
+            dropped: 0,
+            elapsed: 0,
+            offset: 0,
+            height: 10,
+            open_on_error: true,
+            open: false,
+            pos: Vector2::new(0, 0),
+            size: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Light,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.offset = 0;
+    }
+
+    pub fn log(&mut self, level: LogLevel, label: String, source: Option<String>, message: String) {
+        // an element failing every frame keeps a single line
+        let repeated = self.entries.iter_mut().rev().take(DEDUPE_RANGE).find(|entry| {
+            entry.level == level && entry.label == label && entry.source == source && entry.message == message
+        });
+        if let Some(entry) = repeated {
+            entry.count += 1;
+            entry.time = self.elapsed;
+            return;
+        }
+        if self.window_count >= self.rate_limit {
+            self.dropped += 1;
+            return;
+        }
+        self.window_count += 1;
+        self.push(LogEntry { level, label, source, message, count: 1, time: self.elapsed });
+        if level == LogLevel::Error && self.open_on_error {
+            self.open = true;
+        }
+    }
+
+    // source is the name of the element of the error
+    pub fn log_error(&mut self, error: &UiError, source: Option<String>) {
+        self.log(LogLevel::Error, String::from(error.kind()), source, error.to_string());
+    }
+
+    fn push(&mut self, entry: LogEntry) {
+        self.entries.push_back(entry);
+        while self.entries.len() > self.max_entries.max(1) {
+            self.entries.pop_front();
+        }
+        // the scrolled lines stay in place
+        if self.offset > 0 {
+            self.offset += 1;
+        }
+    }
+
+    fn visible_lines(&self) -> i32 {
+        (self.size.y - 2).max(0)
+    }
+
+    fn clamp_offset(&mut self) {
+        self.offset = self.offset.clamp(0, (self.entries.len() as i32 - self.visible_lines()).max(0));
+    }
+
+    // x position of the clear and close buttons
+    fn buttons_x(&self) -> (i32, i32) {
+        let close = self.pos.x + self.size.x - 2 - CLOSE_BUTTON.len() as i32;
+        (close - 1 - CLEAR_BUTTON.len() as i32, close)
+    }
+
+    fn format_entry(entry: &LogEntry) -> (String, String) {
+        let time = format!("{:>8.1}s ", entry.time as f32 / 1000.0);
+        let mut text = format!("{} ", entry.label);
+        if let Some(source) = &entry.source {
+            text.push_str(&format!("[{}] ", source));
+        }
+        text.push_str(&entry.message.replace('\n', " "));
+        if entry.count > 1 {
+            text.push_str(&format!(" (x{})", entry.count));
+        }
+        (time, text)
+    }
+}
+
+impl UiElement for UiConsole {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        if !self.open {
+            return Ok(());
+        }
+        let start = self.get_pos();
+        let end = start + self.size;
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        grid.write_box(start, end - Vector2::new(1, 1), self.box_type);
+        let (clear_x, close_x) = self.buttons_x();
+        grid.write_at(Vector2::new(self.pos.x + 2, self.pos.y), &format!(" Console ({}) ", self.entries.len()));
+        grid.write_at(Vector2::new(clear_x, self.pos.y), CLEAR_BUTTON);
+        grid.write_at(Vector2::new(close_x, self.pos.y), CLOSE_BUTTON);
+
+        // newest entry on the bottom line
+        grid.push_clip(start + Vector2::new(1, 1), end - Vector2::new(1, 1));
+        for line in 0..self.visible_lines() {
+            let index = self.entries.len() as i32 - 1 - self.offset - line;
+            if index < 0 {
+                break;
+            }
+            let entry = &self.entries[index as usize];
+            let (time, text) = UiConsole::format_entry(entry);
+            let line_pos = Vector2::new(start.x + 1, start.y + 1 + line);
+            grid.write_at(line_pos, &time);
+            let text_pos = line_pos + Vector2::new(time.len() as i32, 0);
+            grid.write_at(text_pos, &text);
+            let color = grid.get_named_color(entry.level.get_name()).unwrap_or(self.fg_color);
+            grid.set_fg_from_to(text_pos, text_pos + Vector2::new(entry.label.chars().count() as i32, 1), color.into());
+        }
+        grid.pop_clip();
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, _action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        self.elapsed += app.delta_time;
+        if self.elapsed - self.window_start >= 1000 {
+            if self.dropped > 0 {
+                let message = format!("{} messages dropped over the limit of {} per second", self.dropped, self.rate_limit);
+                self.push(LogEntry { level: LogLevel::Warning, label: String::from(LogLevel::Warning.get_name()), source: None, message, count: 1, time: self.elapsed });
+                self.dropped = 0;
+            }
+            self.window_start = self.elapsed;
+            self.window_count = 0;
+        }
+        if app.is_key_pressed(Key::F12) {
+            self.open = !self.open;
+        }
+        if !self.open {
+            return Ok(());
+        }
+
+        // docked at the bottom of the grid
+        let size = Vector2::new(grid.width as i32, self.height.clamp(3, (grid.height as i32).max(3)));
+        self.pos = Vector2::new(0, size.y - 1);
+        self.size = size;
+
+        if app.mouse_left == 1 && app.grid_position.y == self.pos.y {
+            let (clear_x, close_x) = self.buttons_x();
+            let x = app.grid_position.x;
+            if x >= close_x && x < close_x + CLOSE_BUTTON.len() as i32 {
+                self.open = false;
+                return Ok(());
+            }
+            if x >= clear_x && x < clear_x + CLEAR_BUTTON.len() as i32 {
+                self.clear();
+            }
+        }
+        if self.is_mouse_on_element(app, grid) && app.scroll.y != 0.0 {
+            self.offset += app.scroll.y.round() as i32;
+        }
+        self.clamp_offset();
+        Ok(())
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        self.open &&
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        Vector2::new(0, self.offset)
+    }
+
+    fn set_offset(&mut self, offset: Vector2) {
+        self.offset = offset.y;
+        self.clamp_offset();
+    }
+
+    // the console places itself at the bottom of the grid, only the height is kept
+    fn set_bounds(&mut self, _pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.height = size.y;
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Console);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.box_type = style.box_type;
+    }
+}
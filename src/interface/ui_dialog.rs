@@ -0,0 +1,252 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use glfw::{Key, Modifiers};
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::interface::ui_text::UiText;
+use crate::interface::word::Word;
+use crate::util::grapheme;
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// border and margin around the body
+const MARGIN_X: i32 = 2;
+// border, blank line, buttons and border
+const EXTRA_HEIGHT: i32 = 4;
+const SPACE_BETWEEN_BUTTONS: i32 = 2;
+
+pub struct UiDialog {
+    id: u64,
+    title: String,
+    body: UiText,
+    buttons: Vec<String>,
+    enabled_buttons: Vec<bool>,
+    focused_button: usize,
+    pos: Vector2,
+    size: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+    // colors of the focused button, inverted when none
+    pub focus_colors: Option<StateColors>,
+    pub disabled_colors: StateColors,
+    // the text takes the error color of the theme
+    pub is_error: bool,
+    is_closed: bool,
+}
+
+impl UiDialog {
+    // the dialog is centered on a grid of the given size
+    pub fn new(title: String, body: String, buttons: Vec<String>, grid_size: Vector2) -> Result<UiDialog, UiError> {
+        let buttons_width = buttons.iter().map(|button| button_label(button).chars().count() as i32 + SPACE_BETWEEN_BUTTONS).sum::<i32>();
+        let longest_line = body.lines().map(grapheme::display_width).max().unwrap_or(0);
+        let max_width = grid_size.x * 2 / 3;
+        let width = (longest_line + MARGIN_X * 2)
+            .max(title.chars().count() as i32 + MARGIN_X * 2 + 2)
+            .max(buttons_width + MARGIN_X * 2)
+            .min(max_width);
+        let body_width = width - MARGIN_X * 2;
+        let (_, body_size) = Word::get_word_vec_and_max_size(&body, Vector2::new(0, 0), Vector2::new(body_width, 1))?;
+        let size = Vector2::new(width, body_size.y + EXTRA_HEIGHT);
+        let pos = Vector2::new((grid_size.x - size.x) / 2, (grid_size.y + size.y) / 2 - 1);
+        let body = UiText::new(body, Vector2::new(pos.x + MARGIN_X, pos.y - 1), Vector2::new(body_width, body_size.y))?;
+        Ok(UiDialog {
+            id: 0,
+            title,
+            body,
+            enabled_buttons: vec![true; buttons.len()],
+            buttons,
+            focused_button: 0,
+            pos,
+            size,
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Double,
+            focus_colors: None,
+            disabled_colors: StateColors::new(RGBA8::new(110, 110, 110, 255), RGBA8::new(0, 0, 0, 255)),
+            is_error: false,
+            is_closed: false,
+        })
+    }
+
+    pub fn center(&mut self, grid_size: Vector2) -> Result<(), UiError> {
+        let pos = Vector2::new((grid_size.x - self.size.x) / 2, (grid_size.y - self.size.y) / 2);
+        self.set_bounds(pos, self.size)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.is_closed
+    }
+
+    // a disabled button is drawn with the disabled colors and cannot be pressed
+    pub fn set_button_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(button) = self.enabled_buttons.get_mut(index) {
+            *button = enabled;
+        }
+        if !self.is_button_enabled(self.focused_button) {
+            self.focus_next(1);
+        }
+    }
+
+    fn is_button_enabled(&self, index: usize) -> bool {
+        self.enabled_buttons.get(index).copied().unwrap_or(false)
+    }
+
+    // move the focus by the step to the next enabled button, it stays in place when there is none
+    fn focus_next(&mut self, step: usize) {
+        let count = self.buttons.len().max(1);
+        for i in 1..=count {
+            let index = (self.focused_button + i * step) % count;
+            if self.is_button_enabled(index) {
+                self.focused_button = index;
+                return;
+            }
+        }
+    }
+
+    fn close(&mut self, result: Option<usize>, action_queue: &mut VecDeque<UiAction>) {
+        self.is_closed = true;
+        action_queue.push_back(UiAction::DialogClosed(self.id, result));
+    }
+
+    // x position of each button, the buttons are centered
+    fn button_positions(&self) -> Vec<i32> {
+        let total = self.buttons.iter().map(|button| button_label(button).chars().count() as i32).sum::<i32>()
+            + SPACE_BETWEEN_BUTTONS * (self.buttons.len() as i32 - 1).max(0);
+        let mut x = self.pos.x + (self.size.x - total) / 2;
+        self.buttons.iter().map(|button| {
+            let start = x;
+            x += button_label(button).chars().count() as i32 + SPACE_BETWEEN_BUTTONS;
+            start
+        }).collect()
+    }
+
+    fn buttons_y(&self) -> i32 {
+        self.pos.y - self.size.y + 2
+    }
+}
+
+fn button_label(button: &str) -> String {
+    format!("[ {} ]", button)
+}
+
+impl UiElement for UiDialog {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = self.get_pos();
+        let end = start + self.size;
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        grid.write_box(start, end - Vector2::new(1, 1), self.box_type);
+        grid.write_at(Vector2::new(self.pos.x + MARGIN_X, self.pos.y), &format!(" {} ", self.title));
+        self.body.draw(grid)?;
+        for (i, x) in self.button_positions().iter().enumerate() {
+            let label = button_label(&self.buttons[i]);
+            let button_pos = Vector2::new(*x, self.buttons_y());
+            grid.write_at(button_pos, &label);
+            let button_end = Vector2::new(x + label.chars().count() as i32, self.buttons_y() + 1);
+            if !self.is_button_enabled(i) {
+                StateColors::paint(Some(self.disabled_colors), grid, button_pos, button_end);
+            }
+            else if i == self.focused_button {
+                StateColors::paint(self.focus_colors, grid, button_pos, button_end);
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        self.body.update(app, grid, action_queue)?;
+        // clicks outside the dialog are captured and ignored
+        if app.mouse_left == 1 && app.grid_position.y == self.buttons_y() {
+            let positions = self.button_positions();
+            for (i, x) in positions.iter().enumerate() {
+                let width = button_label(&self.buttons[i]).chars().count() as i32;
+                if app.grid_position.x >= *x && app.grid_position.x < x + width && self.is_button_enabled(i) {
+                    self.close(Some(i), action_queue);
+                    return Ok(());
+                }
+            }
+        }
+        for (key, modifiers) in app.keys.iter() {
+            let count = self.buttons.len().max(1);
+            match key {
+                Key::Escape => self.close(None, action_queue),
+                Key::Enter | Key::KpEnter if self.is_button_enabled(self.focused_button) => self.close(Some(self.focused_button), action_queue),
+                Key::Tab if modifiers.contains(Modifiers::Shift) => self.focus_next(count - 1),
+                Key::Left => self.focus_next(count - 1),
+                Key::Right | Key::Tab => self.focus_next(1),
+                _ => {}
+            }
+            if self.is_closed {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        Vector2::new(0, 0)
+    }
+
+    fn set_offset(&mut self, _offset: Vector2) {
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        let body_pos = Vector2::new(pos.x + MARGIN_X, pos.y + EXTRA_HEIGHT - 1);
+        let body_size = Vector2::new((size.x - MARGIN_X * 2).max(0), (size.y - EXTRA_HEIGHT).max(0));
+        self.body.set_bounds(body_pos, body_size)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Dialog);
+        self.fg_color = if self.is_error { theme.get_color("error") } else { style.normal.fg_color };
+        self.bg_color = style.normal.bg_color;
+        self.focus_colors = Some(style.focus);
+        self.disabled_colors = style.disabled;
+        self.box_type = style.box_type;
+        self.body.set_theme(theme);
+        // the body is drawn on the dialog
+        self.body.fg_color = self.fg_color;
+        self.body.bg_color = self.bg_color;
+    }
+}
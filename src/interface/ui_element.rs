@@ -1,18 +1,79 @@
+use std::any::Any;
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::rc::Rc;
 use crate::{Application, Grid};
 use crate::interface::ui_action::UiAction;
+use crate::interface::theme::Theme;
 use crate::interface::ui_error::UiError;
 use crate::util::vector2::{Vector2};
 
+// counter of the ids of a screen, shared with the containers so the children they get later have their own id
+#[derive(Clone)]
+pub struct IdSource(Rc<Cell<u64>>);
+
+impl IdSource {
+    pub fn new() -> IdSource {
+        IdSource(Rc::new(Cell::new(0)))
+    }
+
+    pub fn next(&self) -> u64 {
+        let id = self.0.get();
+        self.0.set(id + 1);
+        id
+    }
+
+    // give an id to the element and its children, returns the id of the element
+    pub fn assign(&self, ui_element: &mut Box<dyn UiElement>) -> u64 {
+        let id = self.next();
+        ui_element.set_id(id);
+        ui_element.set_id_source(self);
+        for child in ui_element.get_children_mut() {
+            self.assign(child);
+        }
+        id
+    }
+}
+
 pub trait UiElement {
     fn draw(&self, grid: &mut Grid) -> Result<(), UiError>;
     fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError>;
     fn is_mouse_on_element(&self, app: &Application, grid: &Grid) -> bool;
     fn set_id(&mut self, id: u64);
+    fn get_id(&self) -> u64;
+    // give access to the concrete type, see Screen::get
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
     // give the position of the bottom left corner
     fn get_pos(&self) -> Vector2;
     fn get_size(&self) -> Vector2;
     fn get_max_size(&self) -> Vector2;
     fn get_offset(&self) -> Vector2;
     fn set_offset(&mut self, offset: Vector2);
+    // move and resize the element, pos is the bottom left corner
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError>;
+    // elements owned by this one, they are updated and drawn by their parent
+    fn get_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        Vec::new()
+    }
+    // children shown on the grid, only the active one for the tabs
+    fn get_shown_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        self.get_children_mut()
+    }
+    // take out the direct child with the given id, for elements whose children can be removed
+    fn remove_child(&mut self, _id: u64) -> Option<Box<dyn UiElement>> {
+        None
+    }
+    // text of the hovered part of the element, the tooltips of whole elements are set on the screen
+    fn get_tooltip(&self) -> Option<&String> {
+        None
+    }
+    // kept by the elements which take children after being added to the screen
+    fn set_id_source(&mut self, _ids: &IdSource) {}
+    // take the style of the element type from the theme, elements without one only pass it to their children
+    fn set_theme(&mut self, theme: &Theme) {
+        for child in self.get_children_mut() {
+            child.set_theme(theme);
+        }
+    }
 }
\ No newline at end of file
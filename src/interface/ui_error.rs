@@ -1,12 +1,95 @@
+use std::fmt;
 use hex::FromHexError;
 
+// position in a text, line and column start at 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
 #[derive(Clone, Debug)]
 pub enum UiError {
     Error(String),
+    // invalid markup in a text
+    Markup(String, Span),
+    // a file could not be read or written
+    Io(String),
+    // invalid screen file, color or saved state
+    Parse(String),
+    // no element, action, callback or scene with the given id or name
+    NotFound(String),
+    // error of the element with the id, see in_element
+    Element(u64, Box<UiError>),
+}
+
+impl UiError {
+    // name of the kind of error, the one of the wrapped error for an element
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UiError::Error(_) => "error",
+            UiError::Markup(_, _) => "markup",
+            UiError::Io(_) => "io",
+            UiError::Parse(_) => "parse",
+            UiError::NotFound(_) => "not found",
+            UiError::Element(_, error) => error.kind(),
+        }
+    }
+
+    // id of the element the error comes from, if known
+    pub fn get_source(&self) -> Option<u64> {
+        match self {
+            UiError::Element(id, _) => Some(*id),
+            _ => None,
+        }
+    }
+
+    // the error comes from the element, the innermost element is kept
+    pub fn in_element(self, id: u64) -> UiError {
+        match self {
+            UiError::Element(_, _) => self,
+            error => UiError::Element(id, Box::new(error)),
+        }
+    }
+
+    // same kind of error with another message
+    pub fn map_message(self, f: impl FnOnce(String) -> String) -> UiError {
+        match self {
+            UiError::Error(message) => UiError::Error(f(message)),
+            UiError::Markup(message, span) => UiError::Markup(f(message), span),
+            UiError::Io(message) => UiError::Io(f(message)),
+            UiError::Parse(message) => UiError::Parse(f(message)),
+            UiError::NotFound(message) => UiError::NotFound(f(message)),
+            UiError::Element(id, error) => UiError::Element(id, Box::new(error.map_message(f))),
+        }
+    }
+}
+
+impl fmt::Display for UiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UiError::Error(text) | UiError::Io(text) | UiError::Parse(text) | UiError::NotFound(text) => write!(f, "{}", text),
+            UiError::Markup(text, span) => write!(f, "{} at line {}, column {}", text, span.line, span.column),
+            UiError::Element(_, error) => write!(f, "{}", error),
+        }
+    }
 }
 
 impl From<FromHexError> for UiError {
     fn from(err: FromHexError) -> Self {
-        UiError::Error(format!("Hex error: {}", err))
+        UiError::Parse(format!("Hex error: {}", err))
+    }
+}
+
+impl From<toml::de::Error> for UiError {
+    fn from(err: toml::de::Error) -> Self {
+        UiError::Parse(format!("TOML error: {}", err))
+    }
+}
+
+impl From<std::io::Error> for UiError {
+    fn from(err: std::io::Error) -> Self {
+        UiError::Io(format!("IO error: {}", err))
     }
 }
\ No newline at end of file
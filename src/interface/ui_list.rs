@@ -0,0 +1,332 @@
+use std::any::Any;
+use std::collections::{BTreeSet, VecDeque};
+use glfw::{Key, Modifiers};
+use crate::{Application, Grid};
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// time in ms after which the type-ahead buffer is forgotten
+const TYPE_AHEAD_TIMEOUT: u128 = 1000;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SelectionMode {
+    Single,
+    Multi,
+}
+
+pub struct UiListItem {
+    pub text: String,
+    // pushed on the action queue when the item is activated with enter
+    pub action: Option<UiAction>,
+}
+
+impl UiListItem {
+    pub fn new(text: String) -> UiListItem {
+        UiListItem {
+            text,
+            action: None,
+        }
+    }
+}
+
+pub struct UiList {
+    id: u64,
+    items: Vec<UiListItem>,
+    pos: Vector2,
+    size: Vector2,
+    offset: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub selection_color: RGBA8,
+    // colors of the cursor, inverted when none
+    pub focus_colors: Option<StateColors>,
+    pub selection_mode: SelectionMode,
+    // a disabled list ignores the inputs and is drawn with the disabled colors
+    pub enabled: bool,
+    pub disabled_colors: StateColors,
+    cursor: usize,
+    anchor: usize,
+    selected: BTreeSet<usize>,
+    is_focused: bool,
+    // the selection was changed outside of update, it is reported on the next one
+    selection_changed: bool,
+    type_ahead: String,
+    type_ahead_time: u128,
+}
+
+impl UiList {
+    pub fn new(items: Vec<UiListItem>, pos: Vector2, size: Vector2) -> UiList {
+        UiList {
+            id: 0,
+            items,
+            pos,
+            size,
+            offset: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            selection_color: RGBA8::new(0, 95, 175, 255),
+            focus_colors: None,
+            selection_mode: SelectionMode::Single,
+            enabled: true,
+            disabled_colors: StateColors::new(RGBA8::new(110, 110, 110, 255), RGBA8::new(0, 0, 0, 255)),
+            cursor: 0,
+            anchor: 0,
+            selected: BTreeSet::new(),
+            is_focused: false,
+            selection_changed: false,
+            type_ahead: String::new(),
+            type_ahead_time: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_items(&mut self, items: Vec<UiListItem>) {
+        self.items = items;
+        self.selection_changed |= !self.selected.is_empty();
+        self.selected.clear();
+        self.cursor = 0;
+        self.anchor = 0;
+        self.offset = Vector2::new(0, 0);
+    }
+
+    pub fn get_selected(&self) -> Vec<usize> {
+        self.selected.iter().copied().collect()
+    }
+
+    // index of the item drawn on the grid row y, if any
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let row = self.pos.y - y + self.offset.y;
+        if row < 0 || row as usize >= self.items.len() {
+            return None;
+        }
+        Some(row as usize)
+    }
+
+    fn move_cursor(&mut self, index: usize, modifiers: Modifiers) {
+        self.cursor = index;
+        if self.selection_mode == SelectionMode::Multi && modifiers.contains(Modifiers::Shift) {
+            self.select_range(self.anchor, index);
+        }
+        else if self.selection_mode == SelectionMode::Single || !modifiers.contains(Modifiers::Control) {
+            self.select_only(index);
+        }
+    }
+
+    fn select_only(&mut self, index: usize) {
+        self.selected.clear();
+        self.selected.insert(index);
+        self.anchor = index;
+    }
+
+    fn select_range(&mut self, from: usize, to: usize) {
+        self.selected.clear();
+        for index in from.min(to)..=from.max(to) {
+            self.selected.insert(index);
+        }
+    }
+
+    fn toggle(&mut self, index: usize) {
+        if !self.selected.remove(&index) {
+            self.selected.insert(index);
+        }
+        self.anchor = index;
+    }
+
+    fn find_type_ahead(&self) -> Option<usize> {
+        let mut prefix = self.type_ahead.to_lowercase();
+        // a single repeated letter cycles through the items starting with it
+        let first = prefix.chars().next();
+        let cycling = prefix.chars().all(|c| Some(c) == first);
+        if let Some(first) = first.filter(|_| cycling) {
+            prefix = first.to_string();
+        }
+        let start = if cycling { self.cursor + 1 } else { self.cursor };
+        (0..self.items.len())
+            .map(|i| (start + i) % self.items.len())
+            .find(|&i| self.items[i].text.to_lowercase().starts_with(&prefix))
+    }
+
+    fn handle_key(&mut self, key: Key, modifiers: Modifiers, action_queue: &mut VecDeque<UiAction>) {
+        let last = self.items.len() - 1;
+        let page = (self.size.y - 1).max(1) as usize;
+        match key {
+            Key::Up => self.move_cursor(self.cursor.saturating_sub(1), modifiers),
+            Key::Down => self.move_cursor((self.cursor + 1).min(last), modifiers),
+            Key::PageUp => self.move_cursor(self.cursor.saturating_sub(page), modifiers),
+            Key::PageDown => self.move_cursor((self.cursor + page).min(last), modifiers),
+            Key::Home => self.move_cursor(0, modifiers),
+            Key::End => self.move_cursor(last, modifiers),
+            Key::Space if self.selection_mode == SelectionMode::Multi && self.type_ahead.is_empty() => {
+                self.toggle(self.cursor);
+            }
+            Key::Enter | Key::KpEnter => {
+                action_queue.push_back(UiAction::Activated(self.id, vec![self.cursor]));
+                if let Some(action) = &self.items[self.cursor].action {
+                    action_queue.push_back(action.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let cursor = self.cursor as i32;
+        if cursor < self.offset.y {
+            self.offset.y = cursor;
+        }
+        else if cursor >= self.offset.y + self.size.y {
+            self.offset.y = cursor - self.size.y + 1;
+        }
+    }
+}
+
+impl UiElement for UiList {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = self.get_pos();
+        let end = start + self.size;
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        // only the rows inside the element are drawn
+        let first = self.offset.y.max(0) as usize;
+        let last = (first + self.size.y as usize).min(self.items.len());
+        for index in first..last {
+            let y = self.pos.y - (index - first) as i32;
+            let text: String = self.items[index].text.chars().take(self.size.x as usize).collect();
+            grid.write_at(Vector2::new(self.pos.x, y), &text);
+            let row_start = Vector2::new(self.pos.x, y);
+            let row_end = Vector2::new(self.pos.x + self.size.x, y + 1);
+            if self.selected.contains(&index) {
+                grid.set_bg_from_to(row_start, row_end, self.selection_color.into());
+            }
+            if self.is_focused && index == self.cursor {
+                StateColors::paint(self.focus_colors, grid, row_start, row_end);
+            }
+        }
+        if !self.enabled {
+            StateColors::paint(Some(self.disabled_colors), grid, start, end);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if self.selection_changed {
+            self.selection_changed = false;
+            action_queue.push_back(UiAction::SelectionChanged(self.id, self.get_selected()));
+        }
+        if !self.enabled {
+            self.is_focused = false;
+            return Ok(());
+        }
+        if self.items.is_empty() {
+            return Ok(());
+        }
+        let previous_selection = self.selected.clone();
+        let previous_cursor = self.cursor;
+
+        // mouse selection, clicking also gives the focus to the list
+        if app.mouse_left == 1 {
+            self.is_focused = self.is_mouse_on_element(app, grid);
+            if let Some(index) = self.row_at(app.grid_position.y).filter(|_| self.is_focused) {
+                if self.selection_mode == SelectionMode::Multi && app.modifiers.contains(Modifiers::Control) {
+                    self.cursor = index;
+                    self.toggle(index);
+                }
+                else {
+                    self.move_cursor(index, app.modifiers);
+                }
+            }
+        }
+
+        if self.is_focused {
+            for (key, modifiers) in app.keys.iter() {
+                self.handle_key(*key, *modifiers, action_queue);
+            }
+            // type-ahead search
+            self.type_ahead_time += app.delta_time;
+            if self.type_ahead_time > TYPE_AHEAD_TIMEOUT {
+                self.type_ahead.clear();
+            }
+            for c in app.chars.iter() {
+                if c.is_whitespace() && self.type_ahead.is_empty() {
+                    continue;
+                }
+                self.type_ahead.push(*c);
+                self.type_ahead_time = 0;
+                if let Some(index) = self.find_type_ahead() {
+                    self.move_cursor(index, Modifiers::empty());
+                }
+            }
+        }
+        if self.cursor != previous_cursor {
+            self.scroll_to_cursor();
+        }
+
+        if self.selected != previous_selection {
+            action_queue.push_back(UiAction::SelectionChanged(self.id, self.get_selected()));
+        }
+        Ok(())
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        Vector2::new(self.size.x, (self.items.len() as i32).max(self.size.y))
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: Vector2) {
+        self.offset = offset;
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        let max_offset = (self.get_max_size().y - self.size.y).max(0);
+        self.offset.y = self.offset.y.clamp(0, max_offset);
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::List);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.selection_color = theme.get_color("selection");
+        self.focus_colors = Some(style.focus);
+        self.disabled_colors = style.disabled;
+    }
+}
@@ -0,0 +1,226 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::{IdSource, UiElement};
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+pub struct UiPanelChild {
+    // bottom left corner relative to the bottom left corner of the panel content
+    pub pos: Vector2,
+    pub size: Vector2,
+    pub element: Box<dyn UiElement>,
+}
+
+pub struct UiPanel {
+    id: u64,
+    children: Vec<UiPanelChild>,
+    pos: Vector2,
+    size: Vector2,
+    // scrolling of the children when they do not fit in the panel
+    offset: Vector2,
+    // offset the children were placed with, they are moved on the next update when it changes
+    placed_offset: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    box_around: bool,
+    box_type: BoxDrawing,
+    // set once the panel is on a screen
+    ids: Option<IdSource>,
+    // last theme set, given to the children added later
+    theme: Option<Theme>,
+}
+
+impl UiPanel {
+    pub fn new(pos: Vector2, size: Vector2) -> UiPanel {
+        UiPanel {
+            id: 0,
+            children: Vec::new(),
+            pos,
+            size,
+            offset: Vector2::new(0, 0),
+            placed_offset: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_around: false,
+            box_type: BoxDrawing::Light,
+            ids: None,
+            theme: None,
+        }
+    }
+
+    // pos is relative to the bottom left corner of the panel content
+    pub fn add_child(&mut self, mut element: Box<dyn UiElement>, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        if let Some(ids) = &self.ids {
+            ids.assign(&mut element);
+        }
+        if let Some(theme) = &self.theme {
+            element.set_theme(theme);
+        }
+        self.children.push(UiPanelChild { pos, size, element });
+        // the new child may change the scrolled area
+        self.place_children()
+    }
+
+    pub fn set_box_drawing(&mut self, enable: bool, box_type: BoxDrawing) -> Result<(), UiError> {
+        self.box_around = enable;
+        self.box_type = box_type;
+        self.place_children()
+    }
+
+    // bottom left corner of the area given to the children
+    pub fn get_content_pos(&self) -> Vector2 {
+        let border = if self.box_around { 1 } else { 0 };
+        Vector2::new(self.pos.x + border, self.pos.y - self.size.y + 1 + border)
+    }
+
+    pub fn get_content_size(&self) -> Vector2 {
+        let border = if self.box_around { 1 } else { 0 };
+        Vector2::new((self.size.x - border * 2).max(0), (self.size.y - border * 2).max(0))
+    }
+
+    // size of the area covered by the children, at least the content size
+    fn get_children_extent(&self) -> Vector2 {
+        let mut extent = self.get_content_size();
+        for child in self.children.iter() {
+            extent.x = extent.x.max(child.pos.x + child.size.x);
+            extent.y = extent.y.max(child.pos.y + child.size.y);
+        }
+        extent
+    }
+
+    fn place_children(&mut self) -> Result<(), UiError> {
+        let extent = self.get_children_extent();
+        // without offset the top of the children area is shown
+        let shift = Vector2::new(-self.offset.x, self.offset.y - (extent.y - self.get_content_size().y));
+        let content_pos = self.get_content_pos() + shift;
+        for child in self.children.iter_mut() {
+            child.element.set_bounds(content_pos + child.pos, child.size)?;
+        }
+        self.placed_offset = self.offset;
+        Ok(())
+    }
+
+    fn is_mouse_on_content(&self, app: &Application) -> bool {
+        let start = self.get_content_pos();
+        let end = start + self.get_content_size();
+        app.grid_position.x >= start.x && app.grid_position.x < end.x &&
+        app.grid_position.y >= start.y && app.grid_position.y < end.y
+    }
+}
+
+impl UiElement for UiPanel {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = self.get_pos();
+        let end = start + self.size;
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        if self.box_around {
+            grid.write_box(start, end - Vector2::new(1, 1), self.box_type);
+        }
+
+        // children can not draw outside of the panel
+        let content_start = self.get_content_pos();
+        grid.push_clip(content_start, content_start + self.get_content_size());
+        let result = self.children.iter().try_for_each(|child| child.element.draw(grid));
+        grid.pop_clip();
+        result
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if self.offset != self.placed_offset {
+            self.place_children()?;
+        }
+        // the hidden part of a child does not receive the mouse
+        let app_without_mouse;
+        let children_app = if self.is_mouse_on_content(app) {
+            app
+        } else {
+            app_without_mouse = app.without_mouse();
+            &app_without_mouse
+        };
+        for child in self.children.iter_mut() {
+            child.element.update(children_app, grid, action_queue)?;
+        }
+        Ok(())
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        self.size + self.get_children_extent() - self.get_content_size()
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: Vector2) {
+        self.offset = offset;
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        let max_offset = self.get_max_size() - self.size;
+        self.offset.x = self.offset.x.clamp(0, max_offset.x);
+        self.offset.y = self.offset.y.clamp(0, max_offset.y);
+        self.place_children()
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Panel);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.box_type = style.box_type;
+        for child in self.children.iter_mut() {
+            child.element.set_theme(theme);
+        }
+        self.theme = Some(theme.clone());
+    }
+
+    fn get_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        self.children.iter_mut().map(|child| &mut child.element).collect()
+    }
+
+    fn remove_child(&mut self, id: u64) -> Option<Box<dyn UiElement>> {
+        let index = self.children.iter().position(|child| child.element.get_id() == id)?;
+        Some(self.children.remove(index).element)
+    }
+
+    fn set_id_source(&mut self, ids: &IdSource) {
+        self.ids = Some(ids.clone());
+    }
+}
@@ -0,0 +1,242 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::layout::Direction;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// two children divided by a line the mouse can drag
+// a row puts the first child on the left, a column puts it on the top
+pub struct UiSplit {
+    id: u64,
+    direction: Direction,
+    first: Box<dyn UiElement>,
+    second: Box<dyn UiElement>,
+    // share of the first child in the space left by the divider
+    ratio: f32,
+    // cells kept for each child along the direction, the first one wins when both do not fit
+    pub min_first: i32,
+    pub min_second: i32,
+    pos: Vector2,
+    size: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+    // colors of the divider under the mouse or dragged, inverted when none
+    pub hover_colors: Option<StateColors>,
+    is_hovered: bool,
+    dragging: bool,
+    // ratio when the divider was grabbed, a drag ending on it is not reported
+    drag_start_ratio: f32,
+}
+
+impl UiSplit {
+    pub fn new(direction: Direction, first: Box<dyn UiElement>, second: Box<dyn UiElement>, ratio: f32) -> UiSplit {
+        UiSplit {
+            id: 0,
+            direction,
+            first,
+            second,
+            ratio: ratio.clamp(0.0, 1.0),
+            min_first: 1,
+            min_second: 1,
+            pos: Vector2::new(0, 0),
+            size: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Light,
+            hover_colors: None,
+            is_hovered: false,
+            dragging: false,
+            drag_start_ratio: 0.0,
+        }
+    }
+
+    pub fn get_ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    pub fn set_ratio(&mut self, ratio: f32) -> Result<(), UiError> {
+        self.ratio = ratio.clamp(0.0, 1.0);
+        self.place_children()
+    }
+
+    // cells along the direction, the divider takes one
+    fn get_length(&self) -> i32 {
+        match self.direction {
+            Direction::Row => self.size.x,
+            Direction::Column => self.size.y,
+        }
+    }
+
+    fn first_length(&self) -> i32 {
+        let available = (self.get_length() - 1).max(0);
+        let length = (self.ratio * available as f32).round() as i32;
+        length.min(available - self.min_second).max(self.min_first).min(available).max(0)
+    }
+
+    // start and end of the divider line
+    fn divider(&self) -> (Vector2, Vector2) {
+        let first_length = self.first_length();
+        match self.direction {
+            Direction::Row => {
+                let x = self.pos.x + first_length;
+                (Vector2::new(x, self.pos.y - self.size.y + 1), Vector2::new(x + 1, self.pos.y + 1))
+            }
+            Direction::Column => {
+                let y = self.pos.y - first_length;
+                (Vector2::new(self.pos.x, y), Vector2::new(self.pos.x + self.size.x, y + 1))
+            }
+        }
+    }
+
+    fn place_children(&mut self) -> Result<(), UiError> {
+        let first_length = self.first_length();
+        let second_length = (self.get_length() - 1 - first_length).max(0);
+        let bottom = self.pos.y - self.size.y + 1;
+        match self.direction {
+            Direction::Row => {
+                self.first.set_bounds(Vector2::new(self.pos.x, bottom), Vector2::new(first_length, self.size.y))?;
+                self.second.set_bounds(Vector2::new(self.pos.x + first_length + 1, bottom), Vector2::new(second_length, self.size.y))
+            }
+            Direction::Column => {
+                self.first.set_bounds(Vector2::new(self.pos.x, self.pos.y - first_length + 1), Vector2::new(self.size.x, first_length))?;
+                self.second.set_bounds(Vector2::new(self.pos.x, bottom), Vector2::new(self.size.x, second_length))
+            }
+        }
+    }
+
+    fn is_mouse_on_divider(&self, app: &Application) -> bool {
+        let (start, end) = self.divider();
+        app.grid_position.x >= start.x && app.grid_position.x < end.x &&
+        app.grid_position.y >= start.y && app.grid_position.y < end.y
+    }
+
+    // ratio putting the divider under the cursor
+    fn drag_to(&mut self, app: &Application) -> Result<(), UiError> {
+        let first_length = match self.direction {
+            Direction::Row => app.grid_position.x - self.pos.x,
+            Direction::Column => self.pos.y - app.grid_position.y,
+        };
+        let available = (self.get_length() - 1).max(1);
+        let ratio = first_length.clamp(0, available) as f32 / available as f32;
+        if ratio != self.ratio {
+            self.set_ratio(ratio)?;
+        }
+        Ok(())
+    }
+}
+
+impl UiElement for UiSplit {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        self.first.draw(grid)?;
+        self.second.draw(grid)?;
+        let (start, end) = self.divider();
+        let (h_line, v_line, _, _, _, _) = BoxDrawing::get_char(self.box_type);
+        let line = match self.direction {
+            Direction::Row => v_line,
+            Direction::Column => h_line,
+        };
+        grid.write_from_to(start, end, line);
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        if self.is_hovered || self.dragging {
+            StateColors::paint(self.hover_colors, grid, start, end);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        self.is_hovered = self.is_mouse_on_divider(app);
+        if app.mouse_left == 1 && self.is_hovered {
+            self.dragging = true;
+            self.drag_start_ratio = self.ratio;
+        }
+        else if app.mouse_left == 0 && self.dragging {
+            self.dragging = false;
+            // the ratio is saved once the divider is released
+            if self.ratio != self.drag_start_ratio {
+                action_queue.push_back(UiAction::SplitMoved(self.id, self.ratio));
+            }
+        }
+        if self.dragging {
+            self.drag_to(app)?;
+        }
+
+        // the children do not receive the mouse while the divider is dragged
+        let app_without_mouse;
+        let children_app = if self.dragging || self.is_hovered {
+            app_without_mouse = app.without_mouse();
+            &app_without_mouse
+        } else {
+            app
+        };
+        self.first.update(children_app, grid, action_queue)?;
+        self.second.update(children_app, grid, action_queue)
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        Vector2::new(0, 0)
+    }
+
+    fn set_offset(&mut self, _offset: Vector2) {
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        self.place_children()
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Split);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.hover_colors = Some(style.hover);
+        self.box_type = style.box_type;
+        self.first.set_theme(theme);
+        self.second.set_theme(theme);
+    }
+
+    fn get_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        vec![&mut self.first, &mut self.second]
+    }
+}
@@ -0,0 +1,468 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use glfw::Key;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// header and separator line
+const HEADER_HEIGHT: i32 = 2;
+
+#[derive(Copy, Clone)]
+pub enum ColumnWidth {
+    Fixed(i32),
+    // share of the width left by the fixed columns
+    Proportional(u32),
+}
+
+pub struct UiTableColumn {
+    pub title: String,
+    pub width: ColumnWidth,
+}
+
+impl UiTableColumn {
+    pub fn new(title: String, width: ColumnWidth) -> UiTableColumn {
+        UiTableColumn {
+            title,
+            width,
+        }
+    }
+}
+
+struct ColumnResize {
+    column: usize,
+    start_x: i32,
+    start_width: i32,
+}
+
+pub struct UiTable {
+    id: u64,
+    columns: Vec<UiTableColumn>,
+    rows: Vec<Vec<String>>,
+    // display order, order[i] is the index in rows of the i-th displayed row
+    order: Vec<usize>,
+    // column and ascending flag
+    sort: Option<(usize, bool)>,
+    pos: Vector2,
+    size: Vector2,
+    offset: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub header_fg_color: RGBA8,
+    pub header_bg_color: RGBA8,
+    pub selection_color: RGBA8,
+    // colors of the selected row when focused, inverted when none
+    pub focus_colors: Option<StateColors>,
+    pub box_type: BoxDrawing,
+    // a disabled table ignores the inputs and is drawn with the disabled colors
+    pub enabled: bool,
+    pub disabled_colors: StateColors,
+    cursor: usize,
+    selected: Option<usize>,
+    is_focused: bool,
+    resize: Option<ColumnResize>,
+}
+
+impl UiTable {
+    pub fn new(columns: Vec<UiTableColumn>, rows: Vec<Vec<String>>, pos: Vector2, size: Vector2) -> UiTable {
+        let order = (0..rows.len()).collect();
+        UiTable {
+            id: 0,
+            columns,
+            rows,
+            order,
+            sort: None,
+            pos,
+            size,
+            offset: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            header_fg_color: RGBA8::new(0, 0, 0, 255),
+            header_bg_color: RGBA8::new(200, 200, 200, 255),
+            selection_color: RGBA8::new(0, 95, 175, 255),
+            focus_colors: None,
+            box_type: BoxDrawing::Light,
+            enabled: true,
+            disabled_colors: StateColors::new(RGBA8::new(110, 110, 110, 255), RGBA8::new(0, 0, 0, 255)),
+            cursor: 0,
+            selected: None,
+            is_focused: false,
+            resize: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_rows(&mut self, rows: Vec<Vec<String>>) {
+        self.rows = rows;
+        self.order = (0..self.rows.len()).collect();
+        self.selected = None;
+        self.cursor = 0;
+        self.offset = Vector2::new(0, 0);
+        if let Some((column, ascending)) = self.sort {
+            self.sort_by(column, ascending);
+        }
+    }
+
+    pub fn sort_by(&mut self, column: usize, ascending: bool) {
+        let rows = &self.rows;
+        self.order.sort_by(|a, b| {
+            let a = rows[*a].get(column).map(String::as_str).unwrap_or("");
+            let b = rows[*b].get(column).map(String::as_str).unwrap_or("");
+            let ordering = compare_cells(a, b);
+            if ascending { ordering } else { ordering.reverse() }
+        });
+        self.sort = Some((column, ascending));
+        // keep the cursor on the selected row
+        if let Some(selected) = self.selected {
+            self.cursor = self.order.iter().position(|row| *row == selected).unwrap_or(0);
+            self.scroll_to_cursor();
+        }
+    }
+
+    fn visible_rows(&self) -> i32 {
+        (self.size.y - HEADER_HEIGHT).max(0)
+    }
+
+    // width of each column, separators take one cell between columns
+    fn column_widths(&self) -> Vec<i32> {
+        let separators = self.columns.len().saturating_sub(1) as i32;
+        let mut remaining = self.size.x - separators;
+        let mut total_weight = 0;
+        for column in self.columns.iter() {
+            match column.width {
+                ColumnWidth::Fixed(width) => remaining -= width,
+                ColumnWidth::Proportional(weight) => total_weight += weight,
+            }
+        }
+        let remaining = remaining.max(0);
+        // weights of zero share the width equally instead of dividing by zero
+        let equal_weights = total_weight == 0;
+        if equal_weights {
+            total_weight = self.columns.iter().filter(|column| matches!(column.width, ColumnWidth::Proportional(_))).count() as u32;
+        }
+        let mut distributed = 0;
+        let mut weight_seen = 0;
+        self.columns.iter().map(|column| {
+            match column.width {
+                ColumnWidth::Fixed(width) => width,
+                ColumnWidth::Proportional(weight) => {
+                    // cumulative rounding so the proportional columns fill the width exactly
+                    weight_seen += if equal_weights { 1 } else { weight };
+                    let end = (remaining as u64 * weight_seen as u64 / total_weight as u64) as i32;
+                    let width = end - distributed;
+                    distributed = end;
+                    width
+                }
+            }
+        }).collect()
+    }
+
+    // width of all the columns and separators, wider than the element when the fixed columns do not fit
+    fn content_width(&self) -> i32 {
+        let widths = self.column_widths();
+        widths.iter().sum::<i32>() + widths.len().saturating_sub(1) as i32
+    }
+
+    // x position of the first column once scrolled
+    fn columns_x(&self) -> i32 {
+        self.pos.x - self.offset.x
+    }
+
+    // x position of each column separator
+    fn separator_positions(&self) -> Vec<i32> {
+        let mut x = self.columns_x();
+        let widths = self.column_widths();
+        widths.iter().take(widths.len().saturating_sub(1)).map(|width| {
+            x += width;
+            let separator = x;
+            x += 1;
+            separator
+        }).collect()
+    }
+
+    fn column_at(&self, x: i32) -> Option<usize> {
+        let mut start = self.columns_x();
+        for (i, width) in self.column_widths().iter().enumerate() {
+            if x >= start && x < start + width {
+                return Some(i);
+            }
+            start += width + 1;
+        }
+        None
+    }
+
+    // index in the display order of the row drawn on the grid row y, if any
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let row = self.pos.y - HEADER_HEIGHT - y;
+        if row < 0 || row >= self.visible_rows() {
+            return None;
+        }
+        let row = (row + self.offset.y) as usize;
+        if row >= self.order.len() {
+            return None;
+        }
+        Some(row)
+    }
+
+    fn move_cursor(&mut self, index: usize) {
+        self.cursor = index;
+        self.selected = Some(self.order[index]);
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let cursor = self.cursor as i32;
+        if cursor < self.offset.y {
+            self.offset.y = cursor;
+        }
+        else if cursor >= self.offset.y + self.visible_rows() {
+            self.offset.y = cursor - self.visible_rows() + 1;
+        }
+    }
+
+    fn handle_key(&mut self, key: Key, action_queue: &mut VecDeque<UiAction>) {
+        let last = self.order.len() - 1;
+        let page = (self.visible_rows() - 1).max(1) as usize;
+        match key {
+            Key::Up => self.move_cursor(self.cursor.saturating_sub(1)),
+            Key::Down => self.move_cursor((self.cursor + 1).min(last)),
+            Key::PageUp => self.move_cursor(self.cursor.saturating_sub(page)),
+            Key::PageDown => self.move_cursor((self.cursor + page).min(last)),
+            Key::Home => self.move_cursor(0),
+            Key::End => self.move_cursor(last),
+            Key::Enter | Key::KpEnter => {
+                action_queue.push_back(UiAction::Activated(self.id, vec![self.order[self.cursor]]));
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_click(&mut self, app: &Application) {
+        let grid_position = app.grid_position;
+        if let Some(column) = self.separator_positions().iter().position(|x| *x == grid_position.x) {
+            let start_width = self.column_widths()[column];
+            self.resize = Some(ColumnResize {
+                column,
+                start_x: grid_position.x,
+                start_width,
+            });
+        }
+        else if grid_position.y == self.pos.y {
+            if let Some(column) = self.column_at(grid_position.x) {
+                let ascending = match self.sort {
+                    Some((sorted_column, ascending)) if sorted_column == column => !ascending,
+                    _ => true,
+                };
+                self.sort_by(column, ascending);
+            }
+        }
+        else if let Some(row) = self.row_at(grid_position.y) {
+            self.move_cursor(row);
+        }
+    }
+}
+
+impl UiElement for UiTable {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = self.get_pos();
+        let end = start + self.size;
+        let max_x = self.pos.x + self.size.x;
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        // the columns scrolled out of the element are cut
+        grid.push_clip(start, end);
+
+        let (h_line, v_line, _, _, _, _) = BoxDrawing::get_char(self.box_type);
+        let (cross, _, _, _, _) = BoxDrawing::get_junction_char(self.box_type);
+        let widths = self.column_widths();
+        let separators = self.separator_positions();
+
+        // header
+        let header_start = Vector2::new(self.pos.x, self.pos.y);
+        let header_end = Vector2::new(max_x, self.pos.y + 1);
+        grid.set_fg_from_to(header_start, header_end, self.header_fg_color.into());
+        grid.set_bg_from_to(header_start, header_end, self.header_bg_color.into());
+        let mut x = self.columns_x();
+        for (i, column) in self.columns.iter().enumerate() {
+            let title = match self.sort {
+                Some((sorted_column, true)) if sorted_column == i => format!("{} ▲", column.title),
+                Some((sorted_column, false)) if sorted_column == i => format!("{} ▼", column.title),
+                _ => column.title.clone(),
+            };
+            write_cell(grid, Vector2::new(x, self.pos.y), &title, widths[i]);
+            x += widths[i] + 1;
+        }
+        let separator_line_start = Vector2::new(self.pos.x, self.pos.y - 1);
+        grid.write_from_to(separator_line_start, Vector2::new(max_x, self.pos.y), h_line);
+
+        // only the rows inside the element are drawn
+        let first = self.offset.y.max(0) as usize;
+        let last = (first + self.visible_rows() as usize).min(self.order.len());
+        for index in first..last {
+            let y = self.pos.y - HEADER_HEIGHT - (index - first) as i32;
+            let row = &self.rows[self.order[index]];
+            let mut x = self.columns_x();
+            for (i, width) in widths.iter().enumerate() {
+                if x >= max_x {
+                    break;
+                }
+                let text = row.get(i).map(String::as_str).unwrap_or("");
+                write_cell(grid, Vector2::new(x, y), text, *width);
+                x += width + 1;
+            }
+            let row_start = Vector2::new(self.pos.x, y);
+            let row_end = Vector2::new(max_x, y + 1);
+            if self.selected == Some(self.order[index]) {
+                grid.set_bg_from_to(row_start, row_end, self.selection_color.into());
+                if self.is_focused {
+                    StateColors::paint(self.focus_colors, grid, row_start, row_end);
+                }
+            }
+        }
+
+        // column separators
+        for x in separators.iter().filter(|x| **x < max_x) {
+            grid.write_from_to(Vector2::new(*x, start.y), Vector2::new(x + 1, end.y), v_line);
+            grid.write_at(Vector2::new(*x, self.pos.y - 1), &cross.to_string());
+        }
+        if !self.enabled {
+            StateColors::paint(Some(self.disabled_colors), grid, start, end);
+        }
+        grid.pop_clip();
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if !self.enabled {
+            self.is_focused = false;
+            self.resize = None;
+            return Ok(());
+        }
+        let previous_selection = self.selected;
+        let previous_cursor = self.cursor;
+
+        if app.mouse_left == 1 {
+            self.is_focused = self.is_mouse_on_element(app, grid);
+            if self.is_focused {
+                self.handle_click(app);
+            }
+        }
+        else if app.mouse_left == 0 {
+            self.resize = None;
+        }
+
+        // drag a column separator
+        if let Some(resize) = &self.resize {
+            let width = (resize.start_width + app.grid_position.x - resize.start_x).max(1);
+            self.columns[resize.column].width = ColumnWidth::Fixed(width);
+        }
+
+        if self.is_focused && !self.order.is_empty() {
+            for (key, _) in app.keys.iter() {
+                self.handle_key(*key, action_queue);
+            }
+        }
+        if self.cursor != previous_cursor {
+            self.scroll_to_cursor();
+        }
+
+        if self.selected != previous_selection {
+            action_queue.push_back(UiAction::SelectionChanged(self.id, self.selected.into_iter().collect()));
+        }
+        Ok(())
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        Vector2::new(self.content_width().max(self.size.x), (self.order.len() as i32 + HEADER_HEIGHT).max(self.size.y))
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: Vector2) {
+        self.offset = offset;
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        let max_size = self.get_max_size();
+        self.offset.x = self.offset.x.clamp(0, (max_size.x - self.size.x).max(0));
+        self.offset.y = self.offset.y.clamp(0, (max_size.y - self.size.y).max(0));
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Table);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.header_fg_color = theme.get_color("header_fg");
+        self.header_bg_color = theme.get_color("header_bg");
+        self.selection_color = theme.get_color("selection");
+        self.focus_colors = Some(style.focus);
+        self.disabled_colors = style.disabled;
+        self.box_type = style.box_type;
+    }
+}
+
+// numbers are compared by value and come before text, text is compared case insensitively
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.chars().flat_map(char::to_lowercase).cmp(b.chars().flat_map(char::to_lowercase)),
+    }
+}
+
+// write text in a cell of the given width, truncated with an ellipsis when it does not fit
+fn write_cell(grid: &mut Grid, pos: Vector2, text: &str, width: i32) {
+    if width <= 0 {
+        return;
+    }
+    let width = width as usize;
+    let text = if text.chars().count() > width {
+        let mut truncated: String = text.chars().take(width - 1).collect();
+        truncated.push('…');
+        truncated
+    } else {
+        text.to_string()
+    };
+    grid.write_at(pos, &text);
+}
@@ -0,0 +1,367 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use glfw::{Key, Modifiers};
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::{IdSource, UiElement};
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+// top border, titles and baseline
+const STRIP_HEIGHT: i32 = 3;
+
+pub struct UiTab {
+    pub title: String,
+    pub element: Box<dyn UiElement>,
+    pub closable: bool,
+}
+
+impl UiTab {
+    pub fn new(title: String, element: Box<dyn UiElement>) -> UiTab {
+        UiTab {
+            title,
+            element,
+            closable: false,
+        }
+    }
+
+    // width between the two borders
+    fn width(&self) -> i32 {
+        let close_button = if self.closable { 2 } else { 0 };
+        self.title.chars().count() as i32 + 2 + close_button
+    }
+}
+
+pub struct UiTabs {
+    id: u64,
+    tabs: Vec<UiTab>,
+    active: usize,
+    // first tab drawn in the strip when the tabs do not fit the width
+    strip_offset: usize,
+    pos: Vector2,
+    size: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+    // disabled tabs ignore the inputs, the strip is drawn with the disabled colors and the content is not updated
+    pub enabled: bool,
+    pub disabled_colors: StateColors,
+    is_focused: bool,
+    // another tab is shown since the last update, even when the active index stayed the same
+    shown_changed: bool,
+    // set once the tabs are on a screen
+    ids: Option<IdSource>,
+    // last theme set, given to the tabs added later
+    theme: Option<Theme>,
+}
+
+impl UiTabs {
+    pub fn new(tabs: Vec<UiTab>, pos: Vector2, size: Vector2) -> UiTabs {
+        UiTabs {
+            id: 0,
+            tabs,
+            active: 0,
+            strip_offset: 0,
+            pos,
+            size,
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Light,
+            enabled: true,
+            disabled_colors: StateColors::new(RGBA8::new(110, 110, 110, 255), RGBA8::new(0, 0, 0, 255)),
+            is_focused: false,
+            shown_changed: false,
+            ids: None,
+            theme: None,
+        }
+    }
+
+    pub fn get_content_size(&self) -> Vector2 {
+        Vector2::new(self.size.x, (self.size.y - STRIP_HEIGHT).max(0))
+    }
+
+    pub fn add_tab(&mut self, mut tab: UiTab) -> Result<(), UiError> {
+        if let Some(ids) = &self.ids {
+            ids.assign(&mut tab.element);
+        }
+        if let Some(theme) = &self.theme {
+            tab.element.set_theme(theme);
+        }
+        // the tabs may already be laid out
+        tab.element.set_bounds(self.get_pos(), self.get_content_size())?;
+        self.shown_changed |= self.tabs.is_empty();
+        self.tabs.push(tab);
+        Ok(())
+    }
+
+    pub fn get_active(&self) -> usize {
+        self.active
+    }
+
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.shown_changed |= index != self.active;
+            self.active = index;
+            self.scroll_to_active();
+        }
+    }
+
+    pub fn close_tab(&mut self, index: usize) -> Option<UiTab> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        // the next tab takes the place of the closed active one
+        self.shown_changed |= index == self.active;
+        if self.active > index || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        self.strip_offset = self.strip_offset.min(self.tabs.len().saturating_sub(1));
+        self.scroll_to_active();
+        Some(tab)
+    }
+
+    fn is_overflowing(&self) -> bool {
+        let total: i32 = self.tabs.iter().map(|tab| tab.width() + 1).sum::<i32>() + 1;
+        total > self.size.x
+    }
+
+    // x range of the strip where tabs can be drawn, arrows take one cell on each side on overflow
+    fn strip_range(&self) -> (i32, i32) {
+        if self.is_overflowing() {
+            (self.pos.x + 1, self.pos.x + self.size.x - 1)
+        }
+        else {
+            (self.pos.x, self.pos.x + self.size.x)
+        }
+    }
+
+    // left border position of each visible tab, tabs share their borders
+    fn visible_tabs(&self) -> Vec<(usize, i32)> {
+        let (start, end) = self.strip_range();
+        let mut x = start;
+        let mut visible = Vec::new();
+        for (i, tab) in self.tabs.iter().enumerate().skip(self.strip_offset) {
+            if x + tab.width() + 1 >= end && !visible.is_empty() {
+                break;
+            }
+            visible.push((i, x));
+            x += tab.width() + 1;
+        }
+        visible
+    }
+
+    fn scroll_to_active(&mut self) {
+        if self.active < self.strip_offset {
+            self.strip_offset = self.active;
+        }
+        while self.strip_offset < self.active && !self.visible_tabs().iter().any(|(i, _)| *i == self.active) {
+            self.strip_offset += 1;
+        }
+    }
+
+    fn handle_click(&mut self, x: i32) {
+        let (start, end) = self.strip_range();
+        if x < start {
+            self.strip_offset = self.strip_offset.saturating_sub(1);
+            return;
+        }
+        if x >= end {
+            if self.visible_tabs().last().map(|(i, _)| *i + 1 < self.tabs.len()).unwrap_or(false) {
+                self.strip_offset += 1;
+            }
+            return;
+        }
+        for (i, tab_x) in self.visible_tabs() {
+            let width = self.tabs[i].width();
+            if x > tab_x && x <= tab_x + width {
+                // close button is the last character before the right padding
+                if self.tabs[i].closable && x == tab_x + width - 1 {
+                    self.close_tab(i);
+                }
+                else {
+                    self.set_active(i);
+                }
+                return;
+            }
+        }
+    }
+}
+
+impl UiElement for UiTabs {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = Vector2::new(self.pos.x, self.pos.y - STRIP_HEIGHT + 1);
+        let end = Vector2::new(self.pos.x + self.size.x, self.pos.y + 1);
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+
+        let (h_line, v_line, l_l_corner, u_l_corner, l_r_corner, u_r_corner) = BoxDrawing::get_char(self.box_type);
+        let (_, down_tee, up_tee, _, _) = BoxDrawing::get_junction_char(self.box_type);
+        let top = self.pos.y;
+        let title = self.pos.y - 1;
+        let baseline = self.pos.y - 2;
+        grid.write_from_to(Vector2::new(self.pos.x, baseline), Vector2::new(self.pos.x + self.size.x, baseline + 1), h_line);
+
+        let visible = self.visible_tabs();
+        for (n, (i, x)) in visible.iter().enumerate() {
+            let tab = &self.tabs[*i];
+            let right = x + tab.width() + 1;
+            grid.write_from_to(Vector2::new(x + 1, top), Vector2::new(right, top + 1), h_line);
+            let mut text = format!(" {} ", tab.title);
+            if tab.closable {
+                text.push_str("× ");
+            }
+            grid.write_at(Vector2::new(x + 1, title), &text);
+            // borders, joined with the neighbour tabs
+            let left_top = if n == 0 { u_l_corner } else { down_tee };
+            grid.write_at(Vector2::new(*x, top), &left_top.to_string());
+            grid.write_at(Vector2::new(right, top), &u_r_corner.to_string());
+            grid.write_at(Vector2::new(*x, title), &v_line.to_string());
+            grid.write_at(Vector2::new(right, title), &v_line.to_string());
+            if *i == self.active {
+                // the active tab is open on the content
+                grid.write_from_to(Vector2::new(x + 1, baseline), Vector2::new(right, baseline + 1), ' ');
+                let left_bottom = if *x == self.pos.x { v_line } else { l_r_corner };
+                grid.write_at(Vector2::new(*x, baseline), &left_bottom.to_string());
+                grid.write_at(Vector2::new(right, baseline), &l_l_corner.to_string());
+            }
+            else {
+                if n == 0 || visible[n - 1].0 != self.active {
+                    grid.write_at(Vector2::new(*x, baseline), &up_tee.to_string());
+                }
+                grid.write_at(Vector2::new(right, baseline), &up_tee.to_string());
+            }
+        }
+
+        if self.is_overflowing() {
+            grid.write_at(Vector2::new(self.pos.x, title), "◀");
+            grid.write_at(Vector2::new(self.pos.x + self.size.x - 1, title), "▶");
+        }
+        if !self.enabled {
+            StateColors::paint(Some(self.disabled_colors), grid, start, end);
+        }
+
+        if let Some(tab) = self.tabs.get(self.active) {
+            tab.element.draw(grid)?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if !self.enabled {
+            self.is_focused = false;
+        }
+        else if app.mouse_left == 1 {
+            self.is_focused = self.is_mouse_on_element(app, grid);
+            if self.is_focused && app.grid_position.y > self.pos.y - STRIP_HEIGHT {
+                self.handle_click(app.grid_position.x);
+            }
+        }
+
+        if self.is_focused && !self.tabs.is_empty() {
+            for (key, modifiers) in app.keys.iter() {
+                if *key == Key::Tab && modifiers.contains(Modifiers::Control) {
+                    let count = self.tabs.len();
+                    let next = if modifiers.contains(Modifiers::Shift) { self.active + count - 1 } else { self.active + 1 };
+                    self.set_active(next % count);
+                }
+            }
+        }
+
+        if self.shown_changed {
+            self.shown_changed = false;
+            let selected = if self.tabs.is_empty() { Vec::new() } else { vec![self.active] };
+            action_queue.push_back(UiAction::SelectionChanged(self.id, selected));
+        }
+
+        if let Some(tab) = self.tabs.get_mut(self.active).filter(|_| self.enabled) {
+            tab.element.update(app, grid, action_queue)?;
+        }
+        Ok(())
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        Vector2::new(0, 0)
+    }
+
+    fn set_offset(&mut self, _offset: Vector2) {
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        self.scroll_to_active();
+        let content_size = self.get_content_size();
+        for tab in self.tabs.iter_mut() {
+            tab.element.set_bounds(pos, content_size)?;
+        }
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Tabs);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.disabled_colors = style.disabled;
+        self.box_type = style.box_type;
+        for tab in self.tabs.iter_mut() {
+            tab.element.set_theme(theme);
+        }
+        self.theme = Some(theme.clone());
+    }
+
+    fn get_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        self.tabs.iter_mut().map(|tab| &mut tab.element).collect()
+    }
+
+    fn get_shown_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        self.tabs.get_mut(self.active).map(|tab| &mut tab.element).into_iter().collect()
+    }
+
+    // the tab of the element is closed
+    fn remove_child(&mut self, id: u64) -> Option<Box<dyn UiElement>> {
+        let index = self.tabs.iter().position(|tab| tab.element.get_id() == id)?;
+        self.close_tab(index).map(|tab| tab.element)
+    }
+
+    fn set_id_source(&mut self, ids: &IdSource) {
+        self.ids = Some(ids.clone());
+    }
+}
@@ -1,14 +1,19 @@
+use std::any::Any;
 use std::cmp::max;
 use std::collections::VecDeque;
 use crate::{Application, Grid};
 use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::markup::{StyleColor, TextStyle};
 use crate::interface::ui_action::UiAction;
 use crate::interface::ui_element::UiElement;
 use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
 use crate::util::vector2::{Vector2};
-use crate::interface::word::Word;
+use crate::interface::word::{TextAlign, VerticalAlign, Word};
 use crate::util::rgba8::RGBA8;
 
+pub type UpdateFunction = Box<dyn FnMut(&mut UiText, &Application, &Grid) -> Result<(), UiError>>;
+
 pub struct UiText {
     id: u64,
     text: String,
@@ -21,11 +26,25 @@ pub struct UiText {
     pub bg_color: RGBA8,
     box_around: bool,
     box_type: BoxDrawing,
-    pub update_function: fn(&mut UiText, &Application, &Grid) -> Result<(), UiError>,
+    // called at the start of every update
+    pub update_function: Option<UpdateFunction>,
     is_highlighted: bool,
+    // colors of the highlights, inverted when none
+    pub hover_colors: Option<StateColors>,
     pub highlight_on_hover: bool,
     pub highlight_word: bool,
-    pub actions: Vec<UiAction>
+    pub actions: Vec<UiAction>,
+    // texts of the `t markup tags
+    pub tooltips: Vec<String>,
+    // tooltip of the word under the cursor
+    hovered_tooltip: Option<usize>,
+    // scroll one line every given milliseconds and start over at the end
+    pub marquee: Option<u128>,
+    marquee_elapsed: u128,
+    // lines longer than the width are scrolled horizontally instead of wrapped
+    wrap: bool,
+    align: TextAlign,
+    vertical_align: VerticalAlign,
 }
 
 impl UiText {
@@ -43,27 +62,118 @@ impl UiText {
             bg_color: RGBA8::new(0, 0, 0, 255),
             box_around: false,
             box_type: BoxDrawing::Light,
-            update_function: |_ui_text: &mut UiText, _app: &Application, _grid: &Grid| {Ok(())},
+            update_function: None,
             is_highlighted: false,
+            hover_colors: None,
             highlight_on_hover: false,
             highlight_word: true,
             actions: Vec::new(),
+            tooltips: Vec::new(),
+            hovered_tooltip: None,
+            marquee: None,
+            marquee_elapsed: 0,
+            wrap: true,
+            align: TextAlign::Left,
+            vertical_align: VerticalAlign::Top,
         };
         Ok(ui_text)
     }
 
+    // invalid markup keeps the previous text
     pub fn set_text(&mut self, text: String) -> Result<(), UiError>{
-        self.text = text.clone();
-        let (words, max_size) = Word::get_word_vec_and_max_size(&text, self.pos, self.size)?;
+        let previous = std::mem::replace(&mut self.text, text);
+        let (words, max_size) = match self.layout_words() {
+            Ok(layout) => layout,
+            Err(error) => {
+                self.text = previous;
+                return Err(error);
+            }
+        };
         self.words = words;
         self.max_size = max_size;
+        self.clamp_offset();
         Ok(())
     }
 
+    pub fn get_text(&self) -> &String {
+        &self.text
+    }
+
+    pub fn set_update_function(&mut self, update_function: impl FnMut(&mut UiText, &Application, &Grid) -> Result<(), UiError> + 'static) {
+        self.update_function = Some(Box::new(update_function));
+    }
+
     pub fn set_box_drawing(&mut self, enable: bool, box_type: BoxDrawing) {
         self.box_around = enable;
         self.box_type = box_type;
     }
+
+    pub fn set_wrap(&mut self, wrap: bool) -> Result<(), UiError> {
+        self.wrap = wrap;
+        let (words, max_size) = self.layout_words()?;
+        self.words = words;
+        self.max_size = max_size;
+        self.clamp_offset();
+        Ok(())
+    }
+
+    pub fn set_alignment(&mut self, align: TextAlign, vertical_align: VerticalAlign) -> Result<(), UiError> {
+        self.align = align;
+        self.vertical_align = vertical_align;
+        let (words, max_size) = self.layout_words()?;
+        self.words = words;
+        self.max_size = max_size;
+        self.clamp_offset();
+        Ok(())
+    }
+
+    // cells from the left of the text to the end of its longest line
+    pub fn get_content_width(&self) -> i32 {
+        self.words.iter().map(|word| word.pos.x + word.get_width() - self.pos.x).max().unwrap_or(0)
+    }
+
+    fn layout_words(&self) -> Result<(Vec<Word>, Vector2), UiError> {
+        let width = if self.wrap { self.size.x } else { i32::MAX / 2 };
+        let (mut words, mut max_size) = Word::get_word_vec_and_max_size(&self.text, self.pos, Vector2::new(width, self.size.y))?;
+        Word::align(&mut words, self.pos, self.size, self.align, self.vertical_align);
+        let right = words.iter().map(|word| word.pos.x + word.get_width()).max().unwrap_or(self.pos.x);
+        max_size.x = max(right - self.pos.x, self.size.x);
+        Ok((words, max_size))
+    }
+
+    fn draw_words(&self, grid: &mut Grid) -> Result<(), UiError> {
+        for word in self.words.iter() {
+            if word.pos.y < self.pos.y - self.size.y + 1 - self.offset.y || word.pos.y > self.pos.y - self.offset.y {
+                continue;
+            }
+            let start_word = self.word_pos(word);
+            let end_word = start_word + Vector2::new(word.get_width(), 1);
+            grid.write_at(start_word, &word.text);
+            // styles are painted cell by cell
+            for ((style, column), width) in word.styles.iter().zip(word.columns()).zip(word.widths.iter()) {
+                for x in column..column + width {
+                    paint_style(grid, start_word + Vector2::new(x, 0), style)?;
+                }
+            }
+            if let Some(style) = &word.space_style {
+                paint_style(grid, Vector2::new(end_word.x, start_word.y), style)?;
+            }
+            if word.highlight {
+                StateColors::paint(self.hover_colors, grid, start_word, end_word);
+            }
+        }
+        Ok(())
+    }
+
+    // position of the word on the grid once scrolled
+    fn word_pos(&self, word: &Word) -> Vector2 {
+        Vector2::new(word.pos.x - self.offset.x, word.pos.y + self.offset.y)
+    }
+
+    fn clamp_offset(&mut self) {
+        self.offset.x = self.offset.x.clamp(0, (self.max_size.x - self.size.x).max(0));
+        self.offset.y = self.offset.y.clamp(0, (self.max_size.y - self.size.y).max(0));
+    }
 }
 
 impl UiElement for UiText {
@@ -75,21 +185,11 @@ impl UiElement for UiText {
         // set color
         grid.set_fg_from_to(start, end, self.fg_color.into());
         grid.set_bg_from_to(start, end, self.bg_color.into());
-        // draw words
-        for word in self.words.iter() {
-            if word.pos.y < self.pos.y - self.size.y + 1 - self.offset.y || word.pos.y > self.pos.y - self.offset.y {
-                continue;
-            }
-            let start_word = Vector2::new(word.pos.x, word.pos.y) + self.offset;
-            let end_word = Vector2::new(word.pos.x + word.text.len() as i32, word.pos.y + 1) + self.offset;
-            grid.write_at(word.pos + self.offset, &word.text);
-            if let Some(color) = word.fg_color {
-                grid.set_fg_from_to(start_word, end_word, color.into());
-            }
-            if word.highlight {
-                grid.inverse_color_from_to(start_word, end_word);
-            }
-        }
+        // draw words, the ones crossing the edges are cut
+        grid.push_clip(Vector2::new(self.pos.x, self.pos.y - self.size.y + 1), Vector2::new(self.pos.x + self.size.x, self.pos.y + 1));
+        let result = self.draw_words(grid);
+        grid.pop_clip();
+        result?;
 
         if self.box_around {
             let start_box = Vector2::new(self.pos.x - 1, self.pos.y - self.size.y);
@@ -98,17 +198,31 @@ impl UiElement for UiText {
         }
 
         if self.is_highlighted {
-            grid.inverse_color_from_to(start, end);
+            StateColors::paint(self.hover_colors, grid, start, end);
         }
 
         Ok(())
     }
 
     fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError>{
-        self.update_function.call_once((self, app, grid))?;
+        // the function is taken out while it borrows the text
+        if let Some(mut update_function) = self.update_function.take() {
+            let result = update_function(self, app, grid);
+            if self.update_function.is_none() {
+                self.update_function = Some(update_function);
+            }
+            result?;
+        }
         // reset element highlight
         self.is_highlighted = false;
-        self.offset.y = (self.offset.y + 1) % (self.max_size.y - self.size.y + 1);
+        if let Some(interval) = self.marquee {
+            self.marquee_elapsed += app.delta_time;
+            if self.marquee_elapsed >= interval {
+                self.marquee_elapsed = 0;
+                self.offset.y = (self.offset.y + 1) % (self.max_size.y - self.size.y + 1).max(1);
+            }
+        }
+        self.hovered_tooltip = None;
         // reset words highlight
         for word in self.words.iter_mut() {
             word.highlight = false;
@@ -121,21 +235,33 @@ impl UiElement for UiText {
             }
             // find hovered word if any
             let mut hovered_word = None;
-            for word in self.words.iter_mut() {
-                let pos_word = word.pos + self.offset;
-                if pos_word.x <= app.grid_position.x && pos_word.x + (word.text.len() as i32) > app.grid_position.x && pos_word.y == app.grid_position.y {
-                    hovered_word = Some(word);
+            for (i, word) in self.words.iter().enumerate() {
+                let pos_word = self.word_pos(word);
+                if pos_word.y == app.grid_position.y {
+                    if let Some(grapheme_index) = word.grapheme_at(app.grid_position.x - pos_word.x) {
+                        hovered_word = Some((i, grapheme_index));
+                    }
                 }
             }
-            if let Some(word) = hovered_word {
+            if let Some((i, grapheme_index)) = hovered_word {
+                let word = &mut self.words[i];
                 // highlight word
                 if self.highlight_word {
                     word.highlight = true;
                 }
-                // check if mouse is clicked and if yes trigger word action
-                if let Some(action) = word.action {
+                if let Some(tooltip) = word.styles.get(grapheme_index).and_then(|style| style.tooltip) {
+                    if tooltip as usize >= self.tooltips.len() {
+                        return Err(UiError::NotFound(format!("No tooltip {} in the text", tooltip)));
+                    }
+                    self.hovered_tooltip = Some(tooltip as usize);
+                }
+                // check if mouse is clicked and if yes trigger the action of the hovered character
+                if let Some(action) = word.styles.get(grapheme_index).and_then(|style| style.action) {
                     if app.mouse_left == 1 {
-                        action_queue.push_back(self.actions[action as usize].clone());
+                        match self.actions.get(action as usize) {
+                            Some(action) => action_queue.push_back(action.clone()),
+                            None => return Err(UiError::NotFound(format!("No action {} in the text", action))),
+                        }
                     }
                 }
             }
@@ -153,6 +279,18 @@ impl UiElement for UiText {
         self.id = id;
     }
 
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
     fn get_pos(&self) -> Vector2 {
         Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
     }
@@ -172,4 +310,50 @@ impl UiElement for UiText {
     fn set_offset(&mut self, offset: Vector2) {
         self.offset = offset;
     }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        // the box is drawn around the text, it has to fit in the bounds
+        let border = if self.box_around { 1 } else { 0 };
+        self.pos = Vector2::new(pos.x + border, pos.y + size.y - 1 - border);
+        self.size = Vector2::new((size.x - border * 2).max(0), (size.y - border * 2).max(0));
+        let (words, max_size) = self.layout_words()?;
+        self.words = words;
+        self.max_size = max_size;
+        self.clamp_offset();
+        Ok(())
+    }
+
+    fn get_tooltip(&self) -> Option<&String> {
+        self.hovered_tooltip.and_then(|index| self.tooltips.get(index))
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Text);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.hover_colors = Some(style.hover);
+        self.box_type = style.box_type;
+    }
+}
+
+fn paint_style(grid: &mut Grid, pos: Vector2, style: &TextStyle) -> Result<(), UiError> {
+    if let Some(color) = &style.fg_color {
+        let color = resolve_color(grid, color)?;
+        grid.set_fg_at(pos, color.into());
+    }
+    if let Some(color) = &style.bg_color {
+        let color = resolve_color(grid, color)?;
+        grid.set_bg_at(pos, color.into());
+    }
+    if style.attributes != 0 {
+        grid.set_attributes_at(pos, style.attributes);
+    }
+    Ok(())
+}
+
+fn resolve_color(grid: &Grid, color: &StyleColor) -> Result<RGBA8, UiError> {
+    match color {
+        StyleColor::Rgba(color) => Ok(*color),
+        StyleColor::Named(name) => grid.get_named_color(name).ok_or_else(|| UiError::NotFound(format!("No color named {name} in the grid"))),
+    }
 }
\ No newline at end of file
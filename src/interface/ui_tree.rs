@@ -0,0 +1,432 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use glfw::Key;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+pub type TreeLoader = Box<dyn FnMut(&[String]) -> Result<Vec<UiTreeNode>, UiError>>;
+
+pub struct UiTreeNode {
+    pub text: String,
+    pub children: Vec<UiTreeNode>,
+    // children are fetched with the tree loader the first time the node is expanded
+    pub lazy_children: bool,
+    // the loader failed, the node stays without children until the roots are set again
+    pub load_failed: bool,
+    pub is_expanded: bool,
+    // pushed on the action queue when the node is activated with enter
+    pub action: Option<UiAction>,
+}
+
+impl UiTreeNode {
+    pub fn new(text: String, children: Vec<UiTreeNode>) -> UiTreeNode {
+        UiTreeNode {
+            text,
+            children,
+            lazy_children: false,
+            load_failed: false,
+            is_expanded: false,
+            action: None,
+        }
+    }
+
+    pub fn new_lazy(text: String) -> UiTreeNode {
+        let mut node = UiTreeNode::new(text, Vec::new());
+        node.lazy_children = true;
+        node
+    }
+
+    fn has_children(&self) -> bool {
+        self.lazy_children || !self.children.is_empty()
+    }
+}
+
+// a node as displayed, in the order of the rows
+struct TreeRow {
+    path: Vec<usize>,
+    // for each ancestor, true if it is the last of its siblings
+    ancestors_last: Vec<bool>,
+    is_last: bool,
+}
+
+pub struct UiTree {
+    id: u64,
+    roots: Vec<UiTreeNode>,
+    rows: Vec<TreeRow>,
+    pos: Vector2,
+    size: Vector2,
+    offset: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub selection_color: RGBA8,
+    // colors of the cursor, inverted when none
+    pub focus_colors: Option<StateColors>,
+    pub box_type: BoxDrawing,
+    // a disabled tree ignores the inputs and is drawn with the disabled colors
+    pub enabled: bool,
+    pub disabled_colors: StateColors,
+    // receive the texts of the nodes from the root to the expanded node
+    pub loader: TreeLoader,
+    cursor: usize,
+    selected: Option<Vec<usize>>,
+    is_focused: bool,
+}
+
+impl UiTree {
+    pub fn new(roots: Vec<UiTreeNode>, pos: Vector2, size: Vector2) -> UiTree {
+        let mut ui_tree = UiTree {
+            id: 0,
+            roots,
+            rows: Vec::new(),
+            pos,
+            size,
+            offset: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            selection_color: RGBA8::new(0, 95, 175, 255),
+            focus_colors: None,
+            box_type: BoxDrawing::Light,
+            enabled: true,
+            disabled_colors: StateColors::new(RGBA8::new(110, 110, 110, 255), RGBA8::new(0, 0, 0, 255)),
+            loader: Box::new(|_path: &[String]| Ok(Vec::new())),
+            cursor: 0,
+            selected: None,
+            is_focused: false,
+        };
+        ui_tree.build_rows();
+        ui_tree
+    }
+
+    pub fn set_loader(&mut self, loader: impl FnMut(&[String]) -> Result<Vec<UiTreeNode>, UiError> + 'static) {
+        self.loader = Box::new(loader);
+    }
+
+    pub fn set_roots(&mut self, roots: Vec<UiTreeNode>) {
+        self.roots = roots;
+        self.cursor = 0;
+        self.selected = None;
+        self.offset = Vector2::new(0, 0);
+        self.build_rows();
+    }
+
+    pub fn get_node(&self, path: &[usize]) -> Option<&UiTreeNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.roots.get(*first)?;
+        for index in rest {
+            node = node.children.get(*index)?;
+        }
+        Some(node)
+    }
+
+    fn get_node_mut(&mut self, path: &[usize]) -> Option<&mut UiTreeNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.roots.get_mut(*first)?;
+        for index in rest {
+            node = node.children.get_mut(*index)?;
+        }
+        Some(node)
+    }
+
+    pub fn set_expanded(&mut self, path: &[usize], expanded: bool) -> Result<(), UiError> {
+        // texts from the root to the node, given to the loader
+        let texts: Vec<String> = (1..=path.len())
+            .filter_map(|depth| self.get_node(&path[..depth]).map(|node| node.text.clone()))
+            .collect();
+        let load = expanded && self.get_node(path).is_some_and(|node| node.lazy_children);
+        let loaded = if load { Some((self.loader)(&texts)) } else { None };
+        // a failed load is not retried on each expand, the error is given once
+        let mut result = Ok(());
+        if let Some(node) = self.get_node_mut(path) {
+            if let Some(loaded) = loaded {
+                node.lazy_children = false;
+                match loaded {
+                    Ok(children) => node.children = children,
+                    Err(error) => {
+                        node.load_failed = true;
+                        result = Err(error);
+                    }
+                }
+            }
+            node.is_expanded = expanded && node.has_children();
+        }
+        let cursor_path = self.rows.get(self.cursor).map(|row| row.path.clone());
+        self.build_rows();
+        // collapsing can hide the cursor, it then moves to the collapsed node
+        if let Some(cursor_path) = cursor_path {
+            let cursor_path = if cursor_path.starts_with(path) && !expanded { path.to_vec() } else { cursor_path };
+            self.cursor = self.rows.iter().position(|row| row.path == cursor_path).unwrap_or(0);
+        }
+        result
+    }
+
+    fn build_rows(&mut self) {
+        let mut rows = Vec::new();
+        build_rows(&self.roots, &mut Vec::new(), &mut Vec::new(), &mut rows);
+        self.rows = rows;
+        self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+    }
+
+    // guides and expander drawn before the text of the row
+    fn row_prefix(&self, row: &TreeRow) -> String {
+        let (h_line, v_line, l_l_corner, _, _, _) = BoxDrawing::get_char(self.box_type);
+        let (_, _, _, right_tee, _) = BoxDrawing::get_junction_char(self.box_type);
+        let mut prefix = String::new();
+        // roots have no guide
+        for is_last in row.ancestors_last.iter().skip(1) {
+            prefix.push(if *is_last { ' ' } else { v_line });
+            prefix.push(' ');
+        }
+        if !row.ancestors_last.is_empty() {
+            prefix.push(if row.is_last { l_l_corner } else { right_tee });
+            prefix.push(h_line);
+        }
+        if let Some(node) = self.get_node(&row.path) {
+            if node.load_failed {
+                prefix.push('×');
+            }
+            else if node.has_children() {
+                prefix.push(if node.is_expanded { '▾' } else { '▸' });
+            }
+            else {
+                prefix.push(h_line);
+            }
+        }
+        prefix.push(' ');
+        prefix
+    }
+
+    // index of the row drawn on the grid row y, if any
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let row = self.pos.y - y + self.offset.y;
+        if row < 0 || row as usize >= self.rows.len() {
+            return None;
+        }
+        Some(row as usize)
+    }
+
+    fn move_cursor(&mut self, index: usize) {
+        self.cursor = index;
+        self.selected = Some(self.rows[index].path.clone());
+    }
+
+    fn scroll_to_cursor(&mut self) {
+        let cursor = self.cursor as i32;
+        if cursor < self.offset.y {
+            self.offset.y = cursor;
+        }
+        else if cursor >= self.offset.y + self.size.y {
+            self.offset.y = cursor - self.size.y + 1;
+        }
+    }
+
+    fn handle_key(&mut self, key: Key, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        let last = self.rows.len() - 1;
+        let page = (self.size.y - 1).max(1) as usize;
+        let path = self.rows[self.cursor].path.clone();
+        let (is_expanded, has_children) = match self.get_node(&path) {
+            Some(node) => (node.is_expanded, node.has_children()),
+            None => (false, false),
+        };
+        match key {
+            Key::Up => self.move_cursor(self.cursor.saturating_sub(1)),
+            Key::Down => self.move_cursor((self.cursor + 1).min(last)),
+            Key::PageUp => self.move_cursor(self.cursor.saturating_sub(page)),
+            Key::PageDown => self.move_cursor((self.cursor + page).min(last)),
+            Key::Home => self.move_cursor(0),
+            Key::End => self.move_cursor(last),
+            Key::Right if has_children && !is_expanded => self.set_expanded(&path, true)?,
+            // first child
+            Key::Right if is_expanded => self.move_cursor((self.cursor + 1).min(last)),
+            Key::Left if is_expanded => self.set_expanded(&path, false)?,
+            // parent
+            Key::Left if path.len() > 1 => {
+                let parent = &path[..path.len() - 1];
+                if let Some(index) = self.rows.iter().position(|row| row.path == parent) {
+                    self.move_cursor(index);
+                }
+            }
+            Key::Enter | Key::KpEnter => {
+                action_queue.push_back(UiAction::Activated(self.id, path.clone()));
+                if let Some(action) = self.get_node(&path).and_then(|node| node.action.clone()) {
+                    action_queue.push_back(action);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn build_rows(nodes: &[UiTreeNode], path: &mut Vec<usize>, ancestors_last: &mut Vec<bool>, rows: &mut Vec<TreeRow>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let is_last = i == nodes.len() - 1;
+        path.push(i);
+        rows.push(TreeRow {
+            path: path.clone(),
+            ancestors_last: ancestors_last.clone(),
+            is_last,
+        });
+        if node.is_expanded {
+            ancestors_last.push(is_last);
+            build_rows(&node.children, path, ancestors_last, rows);
+            ancestors_last.pop();
+        }
+        path.pop();
+    }
+}
+
+impl UiElement for UiTree {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = self.get_pos();
+        let end = start + self.size;
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        // only the rows inside the element are drawn
+        let first = self.offset.y.max(0) as usize;
+        let last = (first + self.size.y as usize).min(self.rows.len());
+        for index in first..last {
+            let row = &self.rows[index];
+            let y = self.pos.y - (index - first) as i32;
+            let mut text = self.row_prefix(row);
+            let node = self.get_node(&row.path);
+            if let Some(node) = node {
+                text.push_str(&node.text);
+            }
+            let text: String = text.chars().take(self.size.x as usize).collect();
+            grid.write_at(Vector2::new(self.pos.x, y), &text);
+            let row_start = Vector2::new(self.pos.x, y);
+            let row_end = Vector2::new(self.pos.x + self.size.x, y + 1);
+            if node.is_some_and(|node| node.load_failed) {
+                let color = grid.get_named_color("error").unwrap_or(self.fg_color);
+                grid.set_fg_from_to(row_start, row_end, color.into());
+            }
+            if self.selected.as_ref() == Some(&row.path) {
+                grid.set_bg_from_to(row_start, row_end, self.selection_color.into());
+            }
+            if self.is_focused && index == self.cursor {
+                StateColors::paint(self.focus_colors, grid, row_start, row_end);
+            }
+        }
+        if !self.enabled {
+            StateColors::paint(Some(self.disabled_colors), grid, start, end);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if !self.enabled {
+            self.is_focused = false;
+            return Ok(());
+        }
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let previous_selection = self.selected.clone();
+        let previous_cursor = self.cursor;
+        // the first error is returned once the moves and the selection are reported
+        let mut first_error = None;
+
+        // clicking on the guides or the expander toggles the node, clicking on the text selects it
+        if app.mouse_left == 1 {
+            self.is_focused = self.is_mouse_on_element(app, grid);
+            if let Some(index) = self.row_at(app.grid_position.y).filter(|_| self.is_focused) {
+                let prefix_len = self.row_prefix(&self.rows[index]).chars().count() as i32;
+                let path = self.rows[index].path.clone();
+                if app.grid_position.x < self.pos.x + prefix_len {
+                    let is_expanded = self.get_node(&path).map(|node| node.is_expanded).unwrap_or(false);
+                    if let Err(error) = self.set_expanded(&path, !is_expanded) {
+                        first_error.get_or_insert(error);
+                    }
+                }
+                else {
+                    self.move_cursor(index);
+                }
+            }
+        }
+
+        if self.is_focused {
+            for (key, _) in app.keys.iter() {
+                if let Err(error) = self.handle_key(*key, action_queue) {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        if self.cursor != previous_cursor {
+            self.scroll_to_cursor();
+        }
+
+        if self.selected != previous_selection {
+            action_queue.push_back(UiAction::SelectionChanged(self.id, self.selected.clone().unwrap_or_default()));
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.size.y && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        Vector2::new(self.size.x, (self.rows.len() as i32).max(self.size.y))
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: Vector2) {
+        self.offset = offset;
+    }
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        let max_offset = (self.get_max_size().y - self.size.y).max(0);
+        self.offset.y = self.offset.y.clamp(0, max_offset);
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Tree);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.selection_color = theme.get_color("selection");
+        self.focus_colors = Some(style.focus);
+        self.disabled_colors = style.disabled;
+        self.box_type = style.box_type;
+    }
+}
@@ -1,4 +1,6 @@
+use std::any::Any;
 use std::collections::VecDeque;
+use glfw::{Key, Modifiers};
 use crate::Application;
 use crate::interface::ui_action::UiAction;
 use crate::interface::ui_element::UiElement;
@@ -6,64 +8,267 @@ use crate::interface::ui_error::UiError;
 use crate::render::grid::Grid;
 use crate::util::vector2::{Vector2};
 
+#[derive(Copy, Clone, PartialEq)]
+enum Axis {
+    Vertical,
+    Horizontal,
+}
+
 pub struct UiViewport {
     id: u64,
     inside_element: Box<dyn UiElement>,
+    // bottom left corner, the scrollbars are the last column and the first row
+    pos: Vector2,
+    size: Vector2,
+    vertical_scrollbar: bool,
+    horizontal_scrollbar: bool,
+    // lines scrolled by one step of the mouse wheel
+    pub scroll_step: i32,
+    // elements with their own cursor already scroll with the keyboard
+    pub keyboard_scroll: bool,
+    // scrollbar whose handle is grabbed by the mouse and the grabbed cell from the start of the handle
+    dragged_handle: Option<(Axis, i32)>,
+    is_focused: bool,
 }
 
 impl UiViewport {
     pub fn new(inside_element: Box<dyn UiElement>) -> UiViewport {
         UiViewport {
             id: 0,
+            pos: inside_element.get_pos(),
+            size: inside_element.get_size() + Vector2::new(1, 0),
             inside_element,
+            vertical_scrollbar: true,
+            horizontal_scrollbar: false,
+            scroll_step: 3,
+            keyboard_scroll: true,
+            dragged_handle: None,
+            is_focused: false,
+        }
+    }
+
+    pub fn set_scrollbars(&mut self, vertical: bool, horizontal: bool) -> Result<(), UiError> {
+        self.vertical_scrollbar = vertical;
+        self.horizontal_scrollbar = horizontal;
+        self.set_bounds(self.pos, self.size)
+    }
+
+    // put the given line at the top of the view
+    pub fn scroll_to_line(&mut self, line: i32) {
+        self.scroll_to(Axis::Vertical, line);
+    }
+
+    pub fn scroll_by(&mut self, lines: i32) {
+        let line = self.inside_element.get_offset().y + lines;
+        self.scroll_to_line(line);
+    }
+
+    // put the given column at the left of the view
+    pub fn scroll_to_column(&mut self, column: i32) {
+        self.scroll_to(Axis::Horizontal, column);
+    }
+
+    pub fn scroll_by_columns(&mut self, columns: i32) {
+        let column = self.inside_element.get_offset().x + columns;
+        self.scroll_to_column(column);
+    }
+
+    fn scroll_to(&mut self, axis: Axis, value: i32) {
+        let mut offset = self.inside_element.get_offset();
+        let value = value.clamp(0, self.max_offset(axis));
+        match axis {
+            Axis::Vertical => offset.y = value,
+            Axis::Horizontal => offset.x = value,
+        }
+        self.inside_element.set_offset(offset);
+    }
+
+    fn max_offset(&self, axis: Axis) -> i32 {
+        let (_, visible, content, _) = self.axis_values(axis);
+        (content - visible).max(0)
+    }
+
+    // length of the scrollbar, visible and total size of the content and offset along the axis
+    fn axis_values(&self, axis: Axis) -> (i32, i32, i32, i32) {
+        let size = self.inside_element.get_size();
+        let max_size = self.inside_element.get_max_size();
+        let offset = self.inside_element.get_offset();
+        match axis {
+            Axis::Vertical => {
+                let corner = if self.horizontal_scrollbar { 1 } else { 0 };
+                (self.size.y - corner, size.y, max_size.y, offset.y)
+            }
+            Axis::Horizontal => {
+                let corner = if self.vertical_scrollbar { 1 } else { 0 };
+                (self.size.x - corner, size.x, max_size.x, offset.x)
+            }
+        }
+    }
+
+    // cell of the start of the handle from the start of the scrollbar and its size
+    fn handle(&self, axis: Axis) -> (i32, i32) {
+        let (track, visible, content, offset) = self.axis_values(axis);
+        let content = content.max(visible).max(1);
+        let handle_size = ((track as f32 * visible as f32 / content as f32).round() as i32).clamp(1.min(track), track.max(0));
+        let max_offset = self.max_offset(axis);
+        if max_offset == 0 {
+            return (0, handle_size);
+        }
+        let free = track - handle_size;
+        let offset = offset.clamp(0, max_offset);
+        ((offset * free + max_offset / 2) / max_offset, handle_size)
+    }
+
+    // offset placing the start of the handle on the given cell of the scrollbar
+    fn offset_from_handle(&self, axis: Axis, handle_start: i32) -> i32 {
+        let (track, _, _, _) = self.axis_values(axis);
+        let (_, handle_size) = self.handle(axis);
+        let free = track - handle_size;
+        if free <= 0 {
+            return 0;
+        }
+        let handle_start = handle_start.clamp(0, free);
+        (handle_start * self.max_offset(axis) + free / 2) / free
+    }
+
+    // scrollbar under the mouse if any
+    fn scrollbar_at(&self, app: &Application) -> Option<Axis> {
+        let (vertical_track, _, _, _) = self.axis_values(Axis::Vertical);
+        let (horizontal_track, _, _, _) = self.axis_values(Axis::Horizontal);
+        let grid_position = app.grid_position;
+        if self.vertical_scrollbar && grid_position.x == self.pos.x + self.size.x - 1 && grid_position.y >= self.pos.y + self.size.y - vertical_track {
+            return Some(Axis::Vertical);
+        }
+        if self.horizontal_scrollbar && grid_position.y == self.pos.y && grid_position.x < self.pos.x + horizontal_track {
+            return Some(Axis::Horizontal);
         }
+        None
+    }
+
+    // cell of the mouse from the start of the scrollbar, the vertical one starts at the top
+    fn mouse_track_cell(&self, axis: Axis, app: &Application) -> i32 {
+        match axis {
+            Axis::Vertical => self.pos.y + self.size.y - 1 - app.grid_position.y,
+            Axis::Horizontal => app.grid_position.x - self.pos.x,
+        }
+    }
+
+    fn scroll_page(&mut self, axis: Axis, direction: i32) {
+        let (_, visible, _, offset) = self.axis_values(axis);
+        self.scroll_to(axis, offset + visible * direction);
     }
 }
 
 impl UiElement for UiViewport {
     fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
         self.inside_element.draw(grid)?;
-        // draw vertical scrollbar
-        let pos = self.inside_element.get_pos();
-        let size = self.inside_element.get_size();
-        let max_size = self.inside_element.get_max_size();
-        let offset = self.inside_element.get_offset();
-        let background_start = Vector2::new(pos.x + size.x, pos.y);
-        let background_end = Vector2::new(pos.x + size.x + 1, pos.y + size.y);
-        // draw background
-        grid.write_from_to(background_start, background_end, '▒');
-        // get size information
-        // fixme handle does not appear correct
-        let handle_size = (((size.y - 2) as f32 / max_size.y as f32) * (size.y - 2) as f32).floor() as i32;
-        let slide_portion = max_size.y as f32 - size.y as f32;
-        let handle_y = pos.y + size.y - ((offset.y as f32 / slide_portion) * slide_portion).floor() as i32;
-        // draw handle
-        let handle_start = Vector2::new(pos.x + size.x, handle_y - handle_size);
-        let handle_end = Vector2::new(pos.x + size.x + 1, handle_y);
-        grid.write_from_to(handle_start, handle_end, '█');
+        let right = self.pos.x + self.size.x - 1;
+        let top = self.pos.y + self.size.y;
+        if self.vertical_scrollbar {
+            let (track, _, _, _) = self.axis_values(Axis::Vertical);
+            // draw background
+            grid.write_from_to(Vector2::new(right, top - track), Vector2::new(right + 1, top), '▒');
+            // draw handle
+            let (handle_start, handle_size) = self.handle(Axis::Vertical);
+            let handle_top = top - handle_start;
+            grid.write_from_to(Vector2::new(right, handle_top - handle_size), Vector2::new(right + 1, handle_top), '█');
+        }
+        if self.horizontal_scrollbar {
+            let (track, _, _, _) = self.axis_values(Axis::Horizontal);
+            grid.write_from_to(Vector2::new(self.pos.x, self.pos.y), Vector2::new(self.pos.x + track, self.pos.y + 1), '▒');
+            let (handle_start, handle_size) = self.handle(Axis::Horizontal);
+            let handle_left = self.pos.x + handle_start;
+            grid.write_from_to(Vector2::new(handle_left, self.pos.y), Vector2::new(handle_left + handle_size, self.pos.y + 1), '█');
+        }
+        if self.vertical_scrollbar && self.horizontal_scrollbar {
+            // corner between the two scrollbars
+            grid.write_at(Vector2::new(right, self.pos.y), " ");
+        }
         Ok(())
     }
 
     fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if app.mouse_left == 1 {
+            self.is_focused = self.is_mouse_on_element(app, grid);
+            if let Some(axis) = self.scrollbar_at(app) {
+                let cell = self.mouse_track_cell(axis, app);
+                let (handle_start, handle_size) = self.handle(axis);
+                if cell < handle_start {
+                    self.scroll_page(axis, -1);
+                }
+                else if cell >= handle_start + handle_size {
+                    self.scroll_page(axis, 1);
+                }
+                else {
+                    self.dragged_handle = Some((axis, cell - handle_start));
+                }
+            }
+        }
+        if app.mouse_left == 0 {
+            self.dragged_handle = None;
+        }
+        if let Some((axis, grab)) = self.dragged_handle {
+            let offset = self.offset_from_handle(axis, self.mouse_track_cell(axis, app) - grab);
+            self.scroll_to(axis, offset);
+        }
+
+        if self.is_mouse_on_element(app, grid) {
+            // shift turns the wheel into horizontal scrolling
+            let (x, y) = if app.modifiers.contains(Modifiers::Shift) { (app.scroll.y, app.scroll.x) } else { (app.scroll.x, app.scroll.y) };
+            if y != 0.0 {
+                self.scroll_by((-y * self.scroll_step as f64).round() as i32);
+            }
+            if x != 0.0 {
+                self.scroll_by_columns((-x * self.scroll_step as f64).round() as i32);
+            }
+        }
+
+        if self.is_focused && self.keyboard_scroll {
+            for (key, _) in app.keys.iter() {
+                match key {
+                    Key::Up => self.scroll_by(-1),
+                    Key::Down => self.scroll_by(1),
+                    Key::Left => self.scroll_by_columns(-1),
+                    Key::Right => self.scroll_by_columns(1),
+                    Key::PageUp => self.scroll_page(Axis::Vertical, -1),
+                    Key::PageDown => self.scroll_page(Axis::Vertical, 1),
+                    Key::Home => self.scroll_to_line(0),
+                    Key::End => self.scroll_to_line(self.max_offset(Axis::Vertical)),
+                    _ => {}
+                }
+            }
+        }
+
         self.inside_element.update(app, grid, action_queue)
     }
 
-    fn is_mouse_on_element(&self, app: &Application, grid: &Grid) -> bool {
-        todo!()
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y >= self.pos.y && app.grid_position.y < self.pos.y + self.size.y
     }
 
     fn set_id(&mut self, id: u64) {
         self.id = id;
     }
 
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
     fn get_pos(&self) -> Vector2 {
-        self.inside_element.get_pos()
+        self.pos
     }
 
     fn get_size(&self) -> Vector2 {
-        let mut size = self.inside_element.get_size();
-        size.x += 1;
-        size
+        self.size
     }
 
     fn get_max_size(&self) -> Vector2 {
@@ -76,4 +281,24 @@ impl UiElement for UiViewport {
 
     fn set_offset(&mut self, _offset: Vector2) {
     }
-}
\ No newline at end of file
+
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        self.pos = pos;
+        self.size = size;
+        // last column and first row are the scrollbars
+        let vertical = if self.vertical_scrollbar { 1 } else { 0 };
+        let horizontal = if self.horizontal_scrollbar { 1 } else { 0 };
+        let inside_pos = Vector2::new(pos.x, pos.y + horizontal);
+        let inside_size = Vector2::new((size.x - vertical).max(0), (size.y - horizontal).max(0));
+        self.inside_element.set_bounds(inside_pos, inside_size)?;
+        // the content may have shrunk
+        let offset = self.inside_element.get_offset();
+        self.scroll_to(Axis::Vertical, offset.y);
+        self.scroll_to(Axis::Horizontal, offset.x);
+        Ok(())
+    }
+
+    fn get_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        vec![&mut self.inside_element]
+    }
+}
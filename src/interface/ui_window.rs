@@ -0,0 +1,340 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use crate::{Application, Grid};
+use crate::interface::box_drawing::BoxDrawing;
+use crate::interface::ui_action::UiAction;
+use crate::interface::ui_element::UiElement;
+use crate::interface::ui_error::UiError;
+use crate::interface::theme::{StateColors, Theme, Widget};
+use crate::util::grapheme;
+use crate::util::rgba8::RGBA8;
+use crate::util::vector2::{Vector2};
+
+const CLOSE_BUTTON: &str = "[x]";
+const MINIMIZE_BUTTON: &str = "[_]";
+const RESTORE_BUTTON: &str = "[+]";
+// cells of the title bar right of the buttons
+const BUTTONS_MARGIN: i32 = 1;
+const MIN_SIZE: Vector2 = Vector2 { x: 12, y: 3 };
+
+// part of the window grabbed with the mouse, the edges can be combined at the corners
+#[derive(Copy, Clone)]
+struct Drag {
+    cursor: Vector2,
+    // bottom left corner and size when the drag started
+    pos: Vector2,
+    size: Vector2,
+    moving: bool,
+    left: bool,
+    right: bool,
+    bottom: bool,
+}
+
+// floating window added to the screen, moved by its title bar, resized from its edges and raised when clicked
+// closing it only hides it, see Screen::set_visible
+pub struct UiWindow {
+    id: u64,
+    title: String,
+    content: Box<dyn UiElement>,
+    pos: Vector2,
+    size: Vector2,
+    pub fg_color: RGBA8,
+    pub bg_color: RGBA8,
+    pub box_type: BoxDrawing,
+    // colors of the title bar once the window is clicked, inverted when none
+    pub focus_colors: Option<StateColors>,
+    pub closable: bool,
+    pub minimizable: bool,
+    pub resizable: bool,
+    // a disabled window cannot be moved, resized, minimized or closed, its title bar is drawn with the disabled colors
+    // and its content is not updated
+    pub enabled: bool,
+    pub disabled_colors: StateColors,
+    // only the title bar is shown
+    minimized: bool,
+    is_focused: bool,
+    drag: Option<Drag>,
+}
+
+impl UiWindow {
+    // pos is the bottom left corner
+    pub fn new(title: String, content: Box<dyn UiElement>, pos: Vector2, size: Vector2) -> Result<UiWindow, UiError> {
+        let mut window = UiWindow {
+            id: 0,
+            title,
+            content,
+            pos: Vector2::new(0, 0),
+            size: Vector2::new(0, 0),
+            fg_color: RGBA8::new(255, 255, 255, 255),
+            bg_color: RGBA8::new(0, 0, 0, 255),
+            box_type: BoxDrawing::Light,
+            focus_colors: None,
+            closable: true,
+            minimizable: true,
+            resizable: true,
+            enabled: true,
+            disabled_colors: StateColors::new(RGBA8::new(110, 110, 110, 255), RGBA8::new(0, 0, 0, 255)),
+            minimized: false,
+            is_focused: false,
+            drag: None,
+        };
+        window.set_bounds(pos, size)?;
+        Ok(window)
+    }
+
+    // move and shrink the window into a grid of the given size, the title bar stays in place when possible
+    pub fn fit_in(&mut self, grid_size: Vector2) -> Result<(), UiError> {
+        let size = Vector2::new(self.size.x.min(grid_size.x), self.size.y.min(grid_size.y));
+        let pos = Vector2::new(
+            self.pos.x.clamp(0, (grid_size.x - size.x).max(0)),
+            (self.pos.y - size.y + 1).clamp(0, (grid_size.y - size.y).max(0)),
+        );
+        if pos != self.get_pos() || size != self.size {
+            self.set_bounds(pos, size)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_minimized(&mut self, minimized: bool) {
+        self.minimized = minimized;
+        self.drag = None;
+    }
+
+    // rows drawn, only the title bar when minimized
+    fn shown_height(&self) -> i32 {
+        if self.minimized { 1 } else { self.size.y }
+    }
+
+    // x position of the close and minimize buttons, none for the hidden ones
+    fn buttons_x(&self) -> (Option<i32>, Option<i32>) {
+        let right = self.pos.x + self.size.x - 1 - BUTTONS_MARGIN;
+        let close = if self.closable { Some(right - CLOSE_BUTTON.len() as i32) } else { None };
+        let minimize_right = close.unwrap_or(right);
+        let minimize = if self.minimizable { Some(minimize_right - MINIMIZE_BUTTON.len() as i32) } else { None };
+        (close, minimize)
+    }
+
+    fn on_button(x: Option<i32>, label: &str, app: &Application) -> bool {
+        match x {
+            Some(x) => app.grid_position.x >= x && app.grid_position.x < x + label.len() as i32,
+            None => false,
+        }
+    }
+
+    fn is_mouse_on_content(&self, app: &Application) -> bool {
+        !self.minimized &&
+        app.grid_position.x > self.pos.x && app.grid_position.x < self.pos.x + self.size.x - 1 &&
+        app.grid_position.y > self.pos.y - self.size.y + 1 && app.grid_position.y < self.pos.y
+    }
+
+    // grab the title bar or the edges under the cursor
+    fn start_drag(&mut self, app: &Application) {
+        let cursor = app.grid_position;
+        let bottom_left = self.get_pos();
+        let moving = cursor.y == self.pos.y;
+        let resizable = self.resizable && !self.minimized && !moving;
+        let drag = Drag {
+            cursor,
+            pos: bottom_left,
+            size: self.size,
+            moving,
+            left: resizable && cursor.x == bottom_left.x,
+            right: resizable && cursor.x == bottom_left.x + self.size.x - 1,
+            bottom: resizable && cursor.y == bottom_left.y,
+        };
+        if drag.moving || drag.left || drag.right || drag.bottom {
+            self.drag = Some(drag);
+        }
+    }
+
+    // new bounds from the cursor, kept inside the grid
+    fn apply_drag(&mut self, drag: Drag, app: &Application, grid: &Grid) -> Result<(), UiError> {
+        let grid_size = Vector2::new(grid.width as i32, grid.height as i32);
+        let delta = app.grid_position - drag.cursor;
+        let mut pos = drag.pos;
+        let mut size = drag.size;
+        if drag.moving {
+            // a window taller than the grid keeps its title bar on the top row
+            let top = (drag.pos.y + drag.size.y - 1 + delta.y).clamp(self.shown_height() - 1, (grid_size.y - 1).max(self.shown_height() - 1));
+            pos.x = (drag.pos.x + delta.x).clamp(0, (grid_size.x - size.x).max(0));
+            pos.y = top - size.y + 1;
+        }
+        let right = drag.pos.x + drag.size.x;
+        if drag.left {
+            pos.x = (drag.pos.x + delta.x).clamp(0, (right - MIN_SIZE.x).max(0));
+            size.x = right - pos.x;
+        }
+        if drag.right {
+            size.x = (drag.size.x + delta.x).clamp(MIN_SIZE.x, (grid_size.x - drag.pos.x).max(MIN_SIZE.x));
+        }
+        if drag.bottom {
+            let top = drag.pos.y + drag.size.y - 1;
+            pos.y = (drag.pos.y + delta.y).clamp(0, (top - MIN_SIZE.y + 1).max(0));
+            size.y = top - pos.y + 1;
+        }
+        if pos != self.get_pos() || size != self.size {
+            self.set_bounds(pos, size)?;
+        }
+        Ok(())
+    }
+}
+
+impl UiElement for UiWindow {
+    fn draw(&self, grid: &mut Grid) -> Result<(), UiError> {
+        let start = Vector2::new(self.pos.x, self.pos.y - self.shown_height() + 1);
+        let end = Vector2::new(self.pos.x + self.size.x, self.pos.y + 1);
+        grid.write_from_to(start, end, ' ');
+        grid.set_fg_from_to(start, end, self.fg_color.into());
+        grid.set_bg_from_to(start, end, self.bg_color.into());
+        if self.minimized {
+            let (h_line, _, _, u_l_corner, _, u_r_corner) = BoxDrawing::get_char(self.box_type);
+            grid.write_from_to(start, end, h_line);
+            grid.write_at(start, &u_l_corner.to_string());
+            grid.write_at(Vector2::new(end.x - 1, self.pos.y), &u_r_corner.to_string());
+        }
+        else {
+            grid.write_box(start, end - Vector2::new(1, 1), self.box_type);
+            let content_start = Vector2::new(self.pos.x + 1, self.pos.y - self.size.y + 2);
+            let content_end = Vector2::new(self.pos.x + self.size.x - 1, self.pos.y);
+            grid.push_clip(content_start, content_end);
+            let result = self.content.draw(grid);
+            grid.pop_clip();
+            result?;
+        }
+
+        // title cut before the buttons
+        let (close_x, minimize_x) = self.buttons_x();
+        let title_end = minimize_x.or(close_x).unwrap_or(self.pos.x + self.size.x - 1);
+        grid.push_clip(Vector2::new(self.pos.x + 1, self.pos.y), Vector2::new(title_end, self.pos.y + 1));
+        grid.write_at(Vector2::new(self.pos.x + 1, self.pos.y), &format!(" {} ", self.title));
+        grid.pop_clip();
+        if let Some(x) = minimize_x {
+            let label = if self.minimized { RESTORE_BUTTON } else { MINIMIZE_BUTTON };
+            grid.write_at(Vector2::new(x, self.pos.y), label);
+        }
+        if let Some(x) = close_x {
+            grid.write_at(Vector2::new(x, self.pos.y), CLOSE_BUTTON);
+        }
+        if self.is_focused {
+            let title_width = grapheme::display_width(&self.title) + 2;
+            let title_start = Vector2::new(self.pos.x + 1, self.pos.y);
+            let title_stop = Vector2::new((self.pos.x + 1 + title_width).min(title_end), self.pos.y + 1);
+            StateColors::paint(self.focus_colors, grid, title_start, title_stop);
+        }
+        if !self.enabled {
+            StateColors::paint(Some(self.disabled_colors), grid, Vector2::new(self.pos.x, self.pos.y), end);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, app: &Application, grid: &Grid, action_queue: &mut VecDeque<UiAction>) -> Result<(), UiError> {
+        if !self.enabled {
+            self.is_focused = false;
+            self.drag = None;
+            return Ok(());
+        }
+        if app.mouse_left == 1 {
+            self.is_focused = self.is_mouse_on_element(app, grid);
+            if self.is_focused {
+                // in front of the other elements of the screen
+                action_queue.push_back(UiAction::MoveElement(self.id, usize::MAX));
+                let (close_x, minimize_x) = self.buttons_x();
+                if app.grid_position.y == self.pos.y && UiWindow::on_button(close_x, CLOSE_BUTTON, app) {
+                    self.is_focused = false;
+                    action_queue.push_back(UiAction::SetVisible(self.id, false));
+                    return Ok(());
+                }
+                if app.grid_position.y == self.pos.y && UiWindow::on_button(minimize_x, MINIMIZE_BUTTON, app) {
+                    self.set_minimized(!self.minimized);
+                    return Ok(());
+                }
+                self.start_drag(app);
+            }
+        }
+        else if app.mouse_left == 0 {
+            self.drag = None;
+        }
+        if let Some(drag) = self.drag {
+            self.apply_drag(drag, app, grid)?;
+        }
+
+        if self.minimized {
+            return Ok(());
+        }
+        // the content does not receive the mouse while the window is dragged or outside of it
+        let app_without_mouse;
+        let content_app = if self.drag.is_none() && self.is_mouse_on_content(app) {
+            app
+        } else {
+            app_without_mouse = app.without_mouse();
+            &app_without_mouse
+        };
+        self.content.update(content_app, grid, action_queue)
+    }
+
+    fn is_mouse_on_element(&self, app: &Application, _grid: &Grid) -> bool {
+        app.grid_position.x >= self.pos.x && app.grid_position.x < self.pos.x + self.size.x &&
+        app.grid_position.y > self.pos.y - self.shown_height() && app.grid_position.y <= self.pos.y
+    }
+
+    fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_pos(&self) -> Vector2 {
+        Vector2::new(self.pos.x, self.pos.y - self.size.y + 1)
+    }
+
+    fn get_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_max_size(&self) -> Vector2 {
+        self.size
+    }
+
+    fn get_offset(&self) -> Vector2 {
+        Vector2::new(0, 0)
+    }
+
+    fn set_offset(&mut self, _offset: Vector2) {
+    }
+
+    // the content is placed inside the border, the title bar is the top border
+    fn set_bounds(&mut self, pos: Vector2, size: Vector2) -> Result<(), UiError> {
+        let size = Vector2::new(size.x.max(MIN_SIZE.x), size.y.max(MIN_SIZE.y));
+        self.pos = Vector2::new(pos.x, pos.y + size.y - 1);
+        self.size = size;
+        self.content.set_bounds(pos + Vector2::new(1, 1), size - Vector2::new(2, 2))
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let style = theme.get_style(Widget::Window);
+        self.fg_color = style.normal.fg_color;
+        self.bg_color = style.normal.bg_color;
+        self.focus_colors = Some(style.focus);
+        self.disabled_colors = style.disabled;
+        self.box_type = style.box_type;
+        self.content.set_theme(theme);
+    }
+
+    fn get_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        vec![&mut self.content]
+    }
+
+    fn get_shown_children_mut(&mut self) -> Vec<&mut Box<dyn UiElement>> {
+        if self.minimized { Vec::new() } else { vec![&mut self.content] }
+    }
+}
@@ -1,152 +1,193 @@
+use serde::Deserialize;
+use crate::interface::markup::{self, MarkupItem, MarkupWord, TextStyle};
 use crate::interface::ui_error::UiError;
-use crate::util::rgba8::RGBA8;
+use crate::util::grapheme;
 use crate::util::vector2::{Vector2};
 
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+    // wrapped lines are stretched to the width, the last line of a paragraph stays on the left
+    Justify,
+}
+
+#[derive(Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
 pub struct Word {
     pub text: String,
     pub pos: Vector2,
     pub highlight: bool,
-    pub fg_color: Option<RGBA8>,
-    pub action: Option<i32>,
-}
-
-enum ParsingMod {
-    Word,
-    GetParam,
-    Color,
-    Action,
-    Clear,
+    // one style for each grapheme cluster of the text
+    pub styles: Vec<TextStyle>,
+    // cells taken by each grapheme cluster of the text
+    pub widths: Vec<i32>,
+    // style of the space after the word
+    pub space_style: Option<TextStyle>,
+    // last word before a new line of the text or the end
+    pub ends_paragraph: bool,
 }
 
 impl Word {
     pub fn get_word_vec_and_max_size(text: &String, pos: Vector2, size: Vector2) -> Result<(Vec<Word>, Vector2), UiError>
     {
         let words = Word::get_word_vec(text, pos, size)?;
-        let max_y = words.iter().map(|word| word.pos.y).min().unwrap_or(0);
+        let max_y = words.iter().map(|word| word.pos.y).min().unwrap_or(pos.y);
         let max_size = Vector2::new(size.x, (pos.y - max_y + 1).max(size.y));
         Ok((words, max_size))
     }
 
     pub fn get_word_vec(text: &String, pos: Vector2, size: Vector2) -> Result<Vec<Word>, UiError> {
         let mut words = Vec::new();
-        let mut last_word = String::new();
         let mut x_pos = pos.x;
         let mut y_pos = pos.y;
         let max_x = pos.x + size.x;
-        let mut parsing_mod = ParsingMod::Word;
-        let mut color = None;
-        let mut action = None;
-        for (i, c) in text.chars().enumerate() {
-            match parsing_mod {
-                ParsingMod::Word => {
-                    if c == '`' {
-                        parsing_mod = ParsingMod::GetParam;
-                    }
-                    else if c.is_whitespace() || i == text.len() - 1 {
-                        let new_line = c == '\n';
-
-                        if i == text.len() - 1 {
-                            // fixme what if the last char overflow the current line ?
-                            last_word.push(c);
-                        }
-
-                        let word = Word {
-                            text: last_word.clone(),
-                            pos: Vector2::new(x_pos, y_pos),
-                            highlight: false,
-                            fg_color: color,
-                            action,
-                        };
-
-                        if word.text.len() as i32 > 0 {
-                            words.push(word);
-                            x_pos += last_word.len() as i32 + 1;
-                        }
-                        last_word.clear();
-
-                        if new_line {
-                            y_pos += -1;
-                            x_pos = pos.x;
-                        }
-                    }
-                    else {
-                        last_word.push(c);
-                        if x_pos + last_word.len() as i32 > max_x {
+        for item in markup::parse(text)? {
+            match item {
+                MarkupItem::Word(word) => {
+                    // a word longer than the line is broken in pieces, each on its own line
+                    for piece in Word::split(word, size.x.max(1)) {
+                        if x_pos + piece.get_width() > max_x && x_pos != pos.x {
                             x_pos = pos.x;
                             y_pos += -1;
                         }
+                        let width = piece.get_width();
+                        words.push(Word { pos: Vector2::new(x_pos, y_pos), ..piece });
+                        x_pos += width;
                     }
+                    x_pos += 1;
                 }
-                ParsingMod::GetParam => {
-                    match c {
-                        // color flag
-                        'c' => {
-                            parsing_mod = ParsingMod::Color;
-                            last_word.clear();
-                        }
-                        // clear flag
-                        'k' => {
-                            parsing_mod = ParsingMod::Clear;
-                            last_word.clear();
-                        }
-                        // action flag (link)
-                        'l' => {
-                            parsing_mod = ParsingMod::Action;
-                            last_word.clear();
-                        }
-                        '`' => {
-                            parsing_mod = ParsingMod::Word;
-                            last_word.push(c);
-                        }
-                        _ => {
-                            return Err(UiError::Error(format!("Invalid flag {} at character {}", c, i)));
-                        }
+                MarkupItem::NewLine => {
+                    if let Some(word) = words.last_mut() {
+                        word.ends_paragraph = true;
                     }
+                    y_pos += -1;
+                    x_pos = pos.x;
                 }
-                ParsingMod::Color => {
-                    if last_word.len() < 6 {
-                        // color information is 6 characters long
-                        last_word.push(c);
-                    }
-                    else {
-                        // register color
-                        color = Some(RGBA8::from_hex_string(&last_word)?);
-                        last_word.clear();
-                        parsing_mod = ParsingMod::Word;
-                    }
+            }
+        }
+        if let Some(word) = words.last_mut() {
+            word.ends_paragraph = true;
+        }
+        Ok(words)
+    }
+
+    // move the words laid out from the top left corner to their place in the box
+    pub fn align(words: &mut [Word], pos: Vector2, size: Vector2, align: TextAlign, vertical_align: VerticalAlign) {
+        if align != TextAlign::Left {
+            let mut start = 0;
+            while start < words.len() {
+                let y = words[start].pos.y;
+                let end = start + words[start..].iter().take_while(|word| word.pos.y == y).count();
+                Word::align_line(&mut words[start..end], pos.x + size.x, align);
+                start = end;
+            }
+        }
+        let lines = words.iter().map(|word| word.pos.y).min().map(|bottom| pos.y - bottom + 1).unwrap_or(0);
+        let free = (size.y - lines).max(0);
+        let shift = match vertical_align {
+            VerticalAlign::Top => 0,
+            VerticalAlign::Middle => free / 2,
+            VerticalAlign::Bottom => free,
+        };
+        for word in words.iter_mut() {
+            word.pos.y -= shift;
+        }
+    }
+
+    fn align_line(line: &mut [Word], max_x: i32, align: TextAlign) {
+        let right = match line.last() {
+            Some(word) => word.pos.x + word.get_width(),
+            None => return,
+        };
+        // lines wider than the box stay on the left
+        let free = (max_x - right).max(0);
+        match align {
+            TextAlign::Left => {}
+            TextAlign::Center | TextAlign::Right => {
+                let shift = if align == TextAlign::Center { free / 2 } else { free };
+                for word in line.iter_mut() {
+                    word.pos.x += shift;
                 }
-                ParsingMod::Action => {
-                    if c.is_whitespace() {
-                        // todo propagate error
-                        let num_action = last_word.parse::<i32>().expect("Failed to parse action");
-                        action = Some(num_action);
-                        last_word.clear();
-                        parsing_mod = ParsingMod::Word;
-                    }
-                    else {
-                        last_word.push(c);
-                    }
+            }
+            TextAlign::Justify => {
+                let gaps = line.len() as i32 - 1;
+                if gaps == 0 || line[line.len() - 1].ends_paragraph {
+                    return;
                 }
-                ParsingMod::Clear => {
-                    if c.is_whitespace() {
-                        parsing_mod = ParsingMod::Word;
-                    }
-                    else {
-                        match c {
-                            'c' => {
-                                color = None;
-                            }
-                            'l' => {
-                                action = None;
-                            }
-                            _ => {
-                                return Err(UiError::Error(format!("Invalid clear flag {} at character {}", c, i)));
-                            }
-                        }
-                    }
+                // cumulative rounding so the whole width is used
+                for (i, word) in line.iter_mut().enumerate().skip(1) {
+                    let i = i as i32;
+                    word.pos.x += free * i / gaps;
                 }
             }
         }
-        Ok(words)
     }
-}
\ No newline at end of file
+
+    // width of the word on the grid
+    pub fn get_width(&self) -> i32 {
+        self.widths.iter().sum()
+    }
+
+    // index of the grapheme cluster at the given cell from the start of the word
+    pub fn grapheme_at(&self, x: i32) -> Option<usize> {
+        let mut start = 0;
+        for (i, width) in self.widths.iter().enumerate() {
+            if x >= start && x < start + width {
+                return Some(i);
+            }
+            start += width;
+        }
+        None
+    }
+
+    // cell of each grapheme cluster from the start of the word
+    pub fn columns(&self) -> Vec<i32> {
+        self.widths.iter().scan(0, |x, width| {
+            let column = *x;
+            *x += width;
+            Some(column)
+        }).collect()
+    }
+
+    // group the characters in grapheme clusters and cut the word in pieces of at most max_width cells
+    fn split(word: MarkupWord, max_width: i32) -> Vec<Word> {
+        let mut pieces = Vec::new();
+        let mut piece = Word::empty();
+        let mut char_index = 0;
+        for cluster in grapheme::graphemes(&word.text) {
+            if piece.get_width() + cluster.width > max_width && !piece.text.is_empty() {
+                pieces.push(std::mem::replace(&mut piece, Word::empty()));
+            }
+            // a cluster takes the style of its first character
+            piece.styles.push(word.styles.get(char_index).cloned().unwrap_or_default());
+            piece.widths.push(cluster.width);
+            piece.text.push_str(cluster.text);
+            char_index += cluster.text.chars().count();
+        }
+        // only the last piece is followed by a space
+        piece.space_style = word.space_style;
+        pieces.push(piece);
+        pieces
+    }
+
+    fn empty() -> Word {
+        Word {
+            text: String::new(),
+            pos: Vector2::new(0, 0),
+            highlight: false,
+            styles: Vec::new(),
+            widths: Vec::new(),
+            space_style: None,
+            ends_paragraph: false,
+        }
+    }
+}
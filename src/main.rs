@@ -1,5 +1,3 @@
-#![feature(fn_traits)]
-
 mod render;
 mod interface;
 mod util;
@@ -9,15 +7,26 @@ extern crate glfw;
 extern crate core;
 
 use gl::types::*;
-use glfw::{Action, Context, Glfw, Key, MouseButton, OpenGlProfileHint, SwapInterval, Window, WindowHint};
+use glfw::{Action, Context, Glfw, Key, Modifiers, MouseButton, OpenGlProfileHint, SwapInterval, Window, WindowHint};
+use std::cell::Cell;
 use std::ptr;
-use std::time::Instant;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use rusttype::Font;
 use crate::render::grid::Grid;
+use crate::interface::scene_manager::{Scene, SceneManager, Transition};
 use crate::interface::screen::Screen;
+use crate::interface::screen_file::ScreenRegistry;
+use crate::interface::theme::Theme;
+use crate::interface::ui_action::UiAction;
 use crate::util::vector2::{Vector2, Vector2d};
 
+// pixels of a cell, the grid of the screens follows the size of the window
+const CELL_WIDTH: u32 = 8;
+const CELL_HEIGHT: u32 = 16;
+
+#[derive(Clone)]
 pub struct Application {
     aspect_ratio: f32,
     width: u32,
@@ -27,7 +36,40 @@ pub struct Application {
     cursor_position: Vector2d,
     grid_position: Vector2,
     delta_time: u128,
+    // exact duration of the last frame, delta_time is truncated to whole ms
+    frame_time: Duration,
     mouse_left: i32,
+    // keys pressed or repeated during the current frame
+    keys: Vec<(Key, Modifiers)>,
+    // text typed during the current frame
+    chars: Vec<char>,
+    modifiers: Modifiers,
+    // mouse wheel movement during the current frame, positive y is scrolling up
+    scroll: Vector2d,
+}
+
+impl Application {
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.keys.iter().any(|(pressed, _)| *pressed == key)
+    }
+
+    // same state with the cursor outside of every element, clicks still unfocus the elements
+    pub fn without_mouse(&self) -> Application {
+        let mut app = self.clone();
+        app.grid_position = Vector2::new(-1, -1);
+        app.scroll = Vector2d::new(0.0, 0.0);
+        app
+    }
+
+    // same state without any user input, given to the elements behind a modal
+    pub fn without_input(&self) -> Application {
+        let mut app = self.without_mouse();
+        app.mouse_left = 0;
+        app.keys.clear();
+        app.chars.clear();
+        app.modifiers = Modifiers::empty();
+        app
+    }
 }
 
 const VERTEX_SHADER_SOURCE: &[u8] = b"
@@ -36,14 +78,20 @@ in vec3 aVertexPosition;
 in vec2 aTextureCoord;
 in vec4 aFgColor;
 in vec4 aBgColor;
+in vec2 aCellCoord;
+in float aAttributes;
 out vec2 iUv;
 out vec4 iFgColor;
 out vec4 iBgColor;
+out vec2 iCellCoord;
+flat out int iAttributes;
 void main() {
     gl_Position = vec4(aVertexPosition, 1.0);
     iUv = aTextureCoord;
     iFgColor = aFgColor;
     iBgColor = aBgColor;
+    iCellCoord = aCellCoord;
+    iAttributes = int(aAttributes + 0.5);
 }\0";
 
 const FRAGMENT_SHADER_SOURCE: &[u8] = b"
@@ -51,13 +99,50 @@ const FRAGMENT_SHADER_SOURCE: &[u8] = b"
 in vec2 iUv;
 in vec4 iFgColor;
 in vec4 iBgColor;
+in vec2 iCellCoord;
+flat in int iAttributes;
 uniform sampler2D uSampler;
 uniform vec4 uFgColor;
+uniform float uTime;
+// same values as the attributes of render::quad
+const int BOLD = 1;
+const int ITALIC = 2;
+const int UNDERLINE = 4;
+const int BLINK = 8;
+const int REVERSE = 16;
+// sample the glyph of the cell, nothing outside of it
+float glyph(vec2 cell) {
+    if (cell.x < 0.0 || cell.x > 1.0) {
+        return 0.0;
+    }
+    vec2 glyphSize = vec2(8.0, 16.0) / vec2(textureSize(uSampler, 0));
+    vec2 glyphMin = iUv - iCellCoord * glyphSize;
+    return texture(uSampler, glyphMin + cell * glyphSize).x;
+}
 void main() {
-    vec4 textureSample = texture(uSampler, iUv);
+    vec2 cell = iCellCoord;
+    if ((iAttributes & ITALIC) != 0) {
+        cell.x -= (cell.y - 0.5) * 0.25;
+    }
+    float value = glyph(cell);
+    if ((iAttributes & BOLD) != 0) {
+        value = max(value, glyph(cell - vec2(1.0 / 8.0, 0.0)));
+    }
+    if ((iAttributes & UNDERLINE) != 0 && iCellCoord.y < 1.0 / 16.0) {
+        value = 1.0;
+    }
+    if ((iAttributes & BLINK) != 0 && fract(uTime) >= 0.5) {
+        value = 0.0;
+    }
+    vec4 fgColor = iFgColor;
+    vec4 bgColor = iBgColor;
+    if ((iAttributes & REVERSE) != 0) {
+        fgColor = iBgColor;
+        bgColor = iFgColor;
+    }
     // gl_FragColor = textureSample;
     // gl_FragColor = vec4(iUv.x, iUv.y, 1.0, 1.0);
-    gl_FragColor = mix(iBgColor, iFgColor, textureSample.x);
+    gl_FragColor = mix(bgColor, fgColor, value);
 }\0";
 
 const UNIFONT_DATA:&[u8] = include_bytes!("unifont-15.0.01.ttf");
@@ -75,7 +160,12 @@ fn main() {
         cursor_position: Vector2d::new(0.0, 0.0),
         grid_position: Vector2::new(0, 0),
         delta_time: 0,
+        frame_time: Duration::ZERO,
         mouse_left: 0,
+        keys: Vec::new(),
+        chars: Vec::new(),
+        modifiers: Modifiers::empty(),
+        scroll: Vector2d::new(0.0, 0.0),
     };
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS)
@@ -88,7 +178,9 @@ fn main() {
         .expect("Failed to create GLFW window.");
 
     window.set_key_polling(true);
+    window.set_char_polling(true);
     window.set_mouse_button_polling(true);
+    window.set_scroll_polling(true);
     window.make_current();
     window.set_framebuffer_size_polling(true);
     // window.set_size_limits(Some(1280), Some(720), None, None);
@@ -123,42 +215,102 @@ fn main() {
         gl::DeleteShader(fragment_shader);
     }
 
-    let mut screen = Screen::new(shader_program);
+    let mut scenes = SceneManager::new(shader_program);
+    scenes.register("title", |shader_program| file_scene(shader_program, "screens/title.toml"));
+    scenes.register("main", |shader_program| Scene::new(Screen::new(shader_program)));
+    scenes.register("settings", |shader_program| file_scene(shader_program, "screens/settings.toml"));
+    match scenes.create("title") {
+        Ok(scene) => scenes.push(scene, Transition::Cut),
+        Err(error) => panic!("{error}"),
+    }
 
-    while !window.should_close() {
+    // the application ends with its last scene
+    while !window.should_close() && !scenes.is_empty() {
         let start_frame_time = Instant::now();
+        let (grid_width, grid_height) = match scenes.current() {
+            Some(screen) => (screen.grid_width, screen.grid_height),
+            None => break,
+        };
         app.cursor_position = get_mouse_position(&app, &window);
         // grid position
-        let grid_pos_x = (app.cursor_position.x / app.width as f64 * screen.grid_width as f64).floor() as i32;
-        let grid_pos_y = (app.cursor_position.y / app.height as f64 * screen.grid_height as f64).floor() as i32;
+        let grid_pos_x = (app.cursor_position.x / app.width as f64 * grid_width as f64).floor() as i32;
+        let grid_pos_y = (app.cursor_position.y / app.height as f64 * grid_height as f64).floor() as i32;
         app.grid_position = Vector2::new(grid_pos_x, grid_pos_y);
 
         update_mouse_button_state(&mut app);
+        clear_frame_input(&mut app);
 
         for (_, event) in glfw::flush_messages(&events) {
-            handle_window_event(&mut app, &mut window, event);
+            handle_window_event(&mut app, event);
+        }
+        let grid_size = ((app.width / CELL_WIDTH).max(1), (app.height / CELL_HEIGHT).max(1));
+        if grid_size != (grid_width, grid_height) {
+            scenes.resize(grid_size.0, grid_size.1);
         }
 
-        screen.update(&app);
+        // escape closes the dialogs first, then goes back to the previous scene
+        if app.is_key_pressed(Key::Escape) && !scenes.is_transitioning() {
+            if let Some(screen) = scenes.current() {
+                if !screen.has_dialog() {
+                    screen.perform_action(UiAction::PopScene(Transition::Fade(300)));
+                }
+            }
+        }
+        scenes.update(&app);
 
         unsafe {
             gl::Clear(gl::COLOR_BUFFER_BIT);
-            screen.grid.draw();
+            if let Some(screen) = scenes.current() {
+                screen.grid.draw();
+            }
         }
 
         window.swap_buffers();
         glfw.poll_events();
-        app.delta_time = start_frame_time.elapsed().as_millis();
+        app.frame_time = start_frame_time.elapsed();
+        app.delta_time = app.frame_time.as_millis();
+    }
+}
+
+// empty screen filled by a screen file, the errors are shown on the screen
+fn file_scene(shader_program: u32, path: &str) -> Scene {
+    let mut screen = Screen::empty(shader_program);
+    if let Err(error) = screen.load_file(path, &screen_registry()) {
+        screen.action_queue.push_back(UiAction::WriteError(error));
     }
+    Scene::new(screen)
+}
+
+// callbacks the screen files reference by name
+fn screen_registry() -> ScreenRegistry {
+    let mut registry = ScreenRegistry::new();
+    let themes: [fn() -> Theme; 3] = [Theme::dark, Theme::light, Theme::high_contrast];
+    let current = Cell::new(0);
+    registry.add_callback("next_theme", Rc::new(move |screen: &mut Screen| {
+        current.set((current.get() + 1) % themes.len());
+        screen.set_theme(themes[current.get()]());
+        Ok(())
+    }));
+    registry
 }
 
-fn handle_window_event(app: &mut Application, window: &mut Window, event: glfw::WindowEvent) {
+fn handle_window_event(app: &mut Application, event: glfw::WindowEvent) {
     match event {
         // keyboard event
-        glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => window.set_should_close(true),
+        glfw::WindowEvent::Key(key, _, Action::Press | Action::Repeat, modifiers) => {
+            app.keys.push((key, modifiers));
+            app.modifiers = modifiers;
+        }
+        glfw::WindowEvent::Key(_, _, Action::Release, modifiers) => app.modifiers = modifiers,
+        glfw::WindowEvent::Char(c) => app.chars.push(c),
         // left mouse button
-        glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => app.mouse_left = 1,
+        glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Press, modifiers) => {
+            app.mouse_left = 1;
+            app.modifiers = modifiers;
+        }
         glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => app.mouse_left = 0,
+        // mouse wheel
+        glfw::WindowEvent::Scroll(x, y) => app.scroll = app.scroll + Vector2d::new(x, y),
         // window event
         glfw::WindowEvent::FramebufferSize(width, height) => framebuffer_resize_event(app, width as f32, height as f32),
         _ => {}
@@ -181,6 +333,12 @@ fn update_mouse_button_state(app: &mut Application) {
     }
 }
 
+fn clear_frame_input(app: &mut Application) {
+    app.keys.clear();
+    app.chars.clear();
+    app.scroll = Vector2d::new(0.0, 0.0);
+}
+
 fn framebuffer_resize_event(app: &mut Application, width: f32, height:f32) {
     app.window_width = width as u32;
     app.window_height = height as u32;
@@ -262,6 +420,7 @@ fn load_gl_functions(window: &mut Window) {
     gl::ShaderSource::load_with(|_s| window.get_proc_address("glShaderSource"));
     gl::TexImage2D::load_with(|_s| window.get_proc_address("glTexImage2D"));
     gl::TexParameteri::load_with(|_s| window.get_proc_address("glTexParameteri"));
+    gl::Uniform1f::load_with(|_s| window.get_proc_address("glUniform1f"));
     gl::Uniform4f::load_with(|_s| window.get_proc_address("glUniform4f"));
     gl::UseProgram::load_with(|_s| window.get_proc_address("glUseProgram"));
     gl::Viewport::load_with(|_s| window.get_proc_address("glViewport"));
@@ -106,4 +106,12 @@ impl CacheGlyph {
         self.char_to_rect.insert(c, uv_layout);
         uv_layout
     }
+}
+
+impl Drop for CacheGlyph {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
 }
\ No newline at end of file
@@ -1,11 +1,15 @@
+use std::collections::HashMap;
 use std::ffi::c_void;
 use std::mem::size_of;
 use std::ptr;
+use std::time::Instant;
 use gl::types::*;
 use rand::{Rng, thread_rng};
 use crate::interface::box_drawing::BoxDrawing;
 use crate::render::cache_glyph::CacheGlyph;
 use crate::render::quad::Quad;
+use crate::util::grapheme;
+use crate::util::rgba8::RGBA8;
 use crate::util::vector2::{Vector2};
 
 pub struct Grid {
@@ -14,14 +18,28 @@ pub struct Grid {
     vao: u32,
     program: u32,
     nb_vertex: i32,
+    vertex_position_buffer: u32,
     texture_coordinate_buffer: u32,
     texture_coordinate: Vec<f32>,
     fg_color_buffer: u32,
     fg_color: Vec<f32>,
     bg_color_buffer: u32,
     bg_color: Vec<f32>,
+    attributes_buffer: u32,
+    attributes: Vec<f32>,
+    cell_coordinate_buffer: u32,
+    indices_buffer: u32,
+    time_location: GLint,
+    start_time: Instant,
     cache_glyph: CacheGlyph,
-    quads: Vec<Quad>
+    quads: Vec<Quad>,
+    // drawable area as start and end, the last one is used
+    clip_stack: Vec<(Vector2, Vector2)>,
+    // colors referenced by name in the text markup
+    pub named_colors: HashMap<String, RGBA8>,
+    // colors of the cleared cells
+    pub default_fg_color: RGBA8,
+    pub default_bg_color: RGBA8,
 }
 
 impl Grid {
@@ -33,6 +51,8 @@ impl Grid {
         let mut texture_coordinate: Vec<f32> = vec![0.0; (8 * width * height) as usize];
         let mut fg_color: Vec<f32> = vec![0.0; (16 * width * height) as usize];
         let mut bg_color: Vec<f32> = vec![0.0; (16 * width * height) as usize];
+        let mut cell_coordinate: Vec<f32> = vec![0.0; (8 * width * height) as usize];
+        let attributes: Vec<f32> = vec![0.0; (4 * width * height) as usize];
         let mut indices: Vec<u32> = vec![0; (6 * width * height) as usize];
         let mut vp_b_count: usize = 0;
         let mut tc_b_count: usize = 0;
@@ -62,6 +82,8 @@ impl Grid {
                     0.0, 1.0
                 ];
                 texture_coordinate[tc_b_count..(tc_b_count + 8)].copy_from_slice(&texture_coordinate_t);
+                // position inside the cell, used by the shader for the attributes
+                cell_coordinate[tc_b_count..(tc_b_count + 8)].copy_from_slice(&texture_coordinate_t);
 
                 // Can cut the memory cost by 4 using instancing and glVertexAttribPointer, not sure it would give better performance tho
                 let fg_color_t: [f32; 16] = [
@@ -97,44 +119,65 @@ impl Grid {
         let mut texture_coordinate_buffer: u32 = 0;
         let mut fg_color_buffer: u32 = 0;
         let mut bg_color_buffer: u32 = 0;
+        let mut cell_coordinate_buffer: u32 = 0;
+        let mut attributes_buffer: u32 = 0;
         let mut indices_buffer: u32 = 0;
         let vertex_position_attrib_location: GLint;
         let texture_coordinate_attrib_location: GLint;
         let fg_color_attrib_location: GLint;
         let bg_color_attrib_location: GLint;
+        let cell_coordinate_attrib_location: GLint;
+        let attributes_attrib_location: GLint;
+        let time_location: GLint;
 
         unsafe {
             gl::GenVertexArrays(1, &mut vao);
             gl::BindVertexArray(vao);
 
-            vertex_position_attrib_location = gl::GetAttribLocation(program, b"aVertexPosition\0".as_ptr() as *const i8);
+            vertex_position_attrib_location = gl::GetAttribLocation(program, c"aVertexPosition".as_ptr());
             gl::GenBuffers(1, &mut vertex_position_buffer);
             gl::BindBuffer(gl::ARRAY_BUFFER, vertex_position_buffer);
             gl::BufferData(gl::ARRAY_BUFFER, (vertex_position.len() * size_of::<f32>()) as isize, vertex_position.as_ptr() as *const c_void, gl::STATIC_DRAW);
             gl::VertexAttribPointer(vertex_position_attrib_location as GLuint, 3, gl::FLOAT, gl::FALSE, 0, ptr::null::<c_void>());
             gl::EnableVertexAttribArray(vertex_position_attrib_location as GLuint);
 
-            texture_coordinate_attrib_location = gl::GetAttribLocation(program, b"aTextureCoord\0".as_ptr() as *const i8);
+            texture_coordinate_attrib_location = gl::GetAttribLocation(program, c"aTextureCoord".as_ptr());
             gl::GenBuffers(1, &mut texture_coordinate_buffer);
             gl::BindBuffer(gl::ARRAY_BUFFER, texture_coordinate_buffer);
             gl::BufferData(gl::ARRAY_BUFFER, (texture_coordinate.len() * size_of::<f32>()) as isize, texture_coordinate.as_ptr() as *const c_void, gl::DYNAMIC_DRAW);
             gl::VertexAttribPointer(texture_coordinate_attrib_location as GLuint, 2, gl::FLOAT, gl::FALSE, 0, ptr::null::<c_void>());
             gl::EnableVertexAttribArray(texture_coordinate_attrib_location as GLuint);
 
-            fg_color_attrib_location = gl::GetAttribLocation(program, b"aFgColor\0".as_ptr() as *const i8);
+            fg_color_attrib_location = gl::GetAttribLocation(program, c"aFgColor".as_ptr());
             gl::GenBuffers(1, &mut fg_color_buffer);
             gl::BindBuffer(gl::ARRAY_BUFFER, fg_color_buffer);
             gl::BufferData(gl::ARRAY_BUFFER, (fg_color.len() * size_of::<f32>()) as isize, fg_color.as_ptr() as *const c_void, gl::DYNAMIC_DRAW);
             gl::VertexAttribPointer(fg_color_attrib_location as GLuint, 4, gl::FLOAT, gl::FALSE, 0, ptr::null::<c_void>());
             gl::EnableVertexAttribArray(fg_color_attrib_location as GLuint);
 
-            bg_color_attrib_location = gl::GetAttribLocation(program, b"aBgColor\0".as_ptr() as *const i8);
+            bg_color_attrib_location = gl::GetAttribLocation(program, c"aBgColor".as_ptr());
             gl::GenBuffers(1, &mut bg_color_buffer);
             gl::BindBuffer(gl::ARRAY_BUFFER, bg_color_buffer);
             gl::BufferData(gl::ARRAY_BUFFER, (bg_color.len() * size_of::<f32>()) as isize, bg_color.as_ptr() as *const c_void, gl::DYNAMIC_DRAW);
             gl::VertexAttribPointer(bg_color_attrib_location as GLuint, 4, gl::FLOAT, gl::FALSE, 0, ptr::null::<c_void>());
             gl::EnableVertexAttribArray(bg_color_attrib_location as GLuint);
 
+            cell_coordinate_attrib_location = gl::GetAttribLocation(program, c"aCellCoord".as_ptr());
+            gl::GenBuffers(1, &mut cell_coordinate_buffer);
+            gl::BindBuffer(gl::ARRAY_BUFFER, cell_coordinate_buffer);
+            gl::BufferData(gl::ARRAY_BUFFER, (cell_coordinate.len() * size_of::<f32>()) as isize, cell_coordinate.as_ptr() as *const c_void, gl::STATIC_DRAW);
+            gl::VertexAttribPointer(cell_coordinate_attrib_location as GLuint, 2, gl::FLOAT, gl::FALSE, 0, ptr::null::<c_void>());
+            gl::EnableVertexAttribArray(cell_coordinate_attrib_location as GLuint);
+
+            attributes_attrib_location = gl::GetAttribLocation(program, c"aAttributes".as_ptr());
+            gl::GenBuffers(1, &mut attributes_buffer);
+            gl::BindBuffer(gl::ARRAY_BUFFER, attributes_buffer);
+            gl::BufferData(gl::ARRAY_BUFFER, (attributes.len() * size_of::<f32>()) as isize, attributes.as_ptr() as *const c_void, gl::DYNAMIC_DRAW);
+            gl::VertexAttribPointer(attributes_attrib_location as GLuint, 1, gl::FLOAT, gl::FALSE, 0, ptr::null::<c_void>());
+            gl::EnableVertexAttribArray(attributes_attrib_location as GLuint);
+
+            time_location = gl::GetUniformLocation(program, c"uTime".as_ptr());
+
             gl::GenBuffers(1, &mut indices_buffer);
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, indices_buffer);
             gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * size_of::<f32>()) as isize, indices.as_ptr() as *const c_void, gl::STATIC_DRAW);
@@ -144,26 +187,49 @@ impl Grid {
         let quads = vec![Quad {
             char: ' ',
             fg_color: [1.0, 1.0, 1.0, 1.0],
-            bg_color: [0.0, 0.0, 0.0, 1.0]
+            bg_color: [0.0, 0.0, 0.0, 1.0],
+            attributes: 0,
         }; (width * height) as usize];
 
+        let named_colors = HashMap::from([
+            (String::from("info"), RGBA8::new(0, 170, 255, 255)),
+            (String::from("success"), RGBA8::new(0, 200, 80, 255)),
+            (String::from("warning"), RGBA8::new(255, 170, 0, 255)),
+            (String::from("error"), RGBA8::new(255, 50, 50, 255)),
+        ]);
+
         Grid {
             width,
             height,
             vao,
             program,
             nb_vertex: i_b_count as i32,
+            vertex_position_buffer,
             texture_coordinate_buffer,
             texture_coordinate,
             fg_color_buffer,
             fg_color,
             bg_color_buffer,
             bg_color,
+            attributes_buffer,
+            attributes,
+            cell_coordinate_buffer,
+            indices_buffer,
+            time_location,
+            start_time: Instant::now(),
             cache_glyph,
-            quads
+            quads,
+            clip_stack: Vec::new(),
+            named_colors,
+            default_fg_color: RGBA8::new(255, 255, 255, 255),
+            default_bg_color: RGBA8::new(0, 0, 0, 255),
         }
     }
 
+    pub fn get_program(&self) -> u32 {
+        self.program
+    }
+
     pub unsafe fn draw(&mut self) {
 
         for i in 0..(self.width * self.height) {
@@ -179,6 +245,8 @@ impl Grid {
             let fg_color_t = self.quads[i as usize].fg_color;
             let bg_color = &mut self.bg_color;
             let bg_color_t = self.quads[i as usize].bg_color;
+            let attributes_t = self.quads[i as usize].attributes as f32;
+            self.attributes[(i * 4) as usize..(i * 4 + 4) as usize].fill(attributes_t);
             for i in 0..4 {
                 bg_color[offset_c + i * 4]     = bg_color_t[0];
                 bg_color[offset_c + i * 4 + 1] = bg_color_t[1];
@@ -197,9 +265,12 @@ impl Grid {
         gl::BufferSubData(gl::ARRAY_BUFFER, 0, (self.fg_color.len() * size_of::<f32>()) as isize, self.fg_color.as_ptr() as *const c_void);
         gl::BindBuffer(gl::ARRAY_BUFFER, self.bg_color_buffer);
         gl::BufferSubData(gl::ARRAY_BUFFER, 0, (self.bg_color.len() * size_of::<f32>()) as isize, self.bg_color.as_ptr() as *const c_void);
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.attributes_buffer);
+        gl::BufferSubData(gl::ARRAY_BUFFER, 0, (self.attributes.len() * size_of::<f32>()) as isize, self.attributes.as_ptr() as *const c_void);
 
         self.cache_glyph.update_texture();
         gl::UseProgram(self.program);
+        gl::Uniform1f(self.time_location, self.start_time.elapsed().as_secs_f32());
         gl::BindVertexArray(self.vao);
         gl::BindTexture(gl::TEXTURE_2D, self.cache_glyph.texture);
         gl::DrawElements(gl::TRIANGLES, self.nb_vertex, gl::UNSIGNED_INT, ptr::null());
@@ -212,8 +283,9 @@ impl Grid {
     pub fn clear(&mut self) {
         for char in self.quads.as_mut_slice() {
             char.switch_char(' ');
-            char.switch_fg_color([1.0, 1.0, 1.0, 1.0]);
-            char.switch_bg_color([0.0, 0.0, 0.0, 1.0]);
+            char.switch_fg_color(self.default_fg_color.into());
+            char.switch_bg_color(self.default_bg_color.into());
+            char.switch_attributes(0);
         }
     }
 
@@ -225,104 +297,188 @@ impl Grid {
 
     pub fn clear_fg_color(&mut self) {
         for char in self.quads.as_mut_slice() {
-            char.switch_fg_color([1.0, 1.0, 1.0, 1.0]);
+            char.switch_fg_color(self.default_fg_color.into());
         }
     }
 
     pub fn clear_bg_color(&mut self) {
         for quad in self.quads.as_mut_slice() {
-            quad.switch_bg_color([0.0, 0.0, 0.0, 1.0]);
+            quad.switch_bg_color(self.default_bg_color.into());
         }
     }
 
-    pub fn write_at(&mut self, pos: Vector2, text: &str) {
-        let text_vec: Vec<char> = text.chars().collect();
-        let mut text_len = text_vec.len() as i32;
-        let start_position = pos.y * self.width as i32 + pos.x;
-        if start_position + text_len > self.quads.len() as i32 - 1 {
-            let to_trim = start_position + (text_len - 1) - (self.quads.len() as i32 - 1);
-            text_len -= to_trim;
+    // only the cells inside the intersection of the given area and the current clip are written until pop_clip
+    pub fn push_clip(&mut self, start: Vector2, end: Vector2) {
+        let clip = match self.clip_stack.last() {
+            Some((clip_start, clip_end)) => (
+                Vector2::new(start.x.max(clip_start.x), start.y.max(clip_start.y)),
+                Vector2::new(end.x.min(clip_end.x), end.y.min(clip_end.y)),
+            ),
+            None => (start, end),
+        };
+        self.clip_stack.push(clip);
+    }
+
+    pub fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+    }
+
+    // index of the quad at the given position, none when outside the grid or the clip
+    fn get_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        if let Some((start, end)) = self.clip_stack.last() {
+            if x < start.x || y < start.y || x >= end.x || y >= end.y {
+                return None;
+            }
         }
-        for text_index in 0..text_len {
-            let char = &mut self.quads[(start_position + text_index) as usize];
-            char.switch_char(text_vec[text_index as usize]);
+        Some((x + y * self.width as i32) as usize)
+    }
+
+    // one grapheme cluster per cell, drawn with its base character, wide ones leave the next cell blank
+    pub fn write_at(&mut self, pos: Vector2, text: &str) {
+        let mut x = pos.x;
+        for cluster in grapheme::graphemes(text) {
+            let c = cluster.text.chars().next().unwrap_or(' ');
+            if let Some(index) = self.get_index(x, pos.y) {
+                self.quads[index].switch_char(c);
+            }
+            for blank_x in x + 1..x + cluster.width {
+                if let Some(index) = self.get_index(blank_x, pos.y) {
+                    self.quads[index].switch_char(' ');
+                }
+            }
+            x += cluster.width;
         }
     }
 
     pub fn write_box(&mut self, start: Vector2, end: Vector2, box_style: BoxDrawing) {
-        let char = self.quads.as_mut_slice();
         let (h_line, v_line, l_l_corner, u_l_corner, l_r_corner, u_r_corner) = BoxDrawing::get_char(box_style);
         for x in start.x..=end.x {
             for y in start.y..=end.y {
-                let index = (y * self.width as i32 + x) as usize;
-                if (x != start.x && x != end.x && y != start.y && y != end.y) || index > (self.width * self.height) as usize {
+                if x != start.x && x != end.x && y != start.y && y != end.y {
                     continue;
                 }
+                let index = match self.get_index(x, y) {
+                    Some(index) => index,
+                    None => continue,
+                };
+                let char = &mut self.quads[index];
                 if x == start.x && y == start.y {
-                    char[index].switch_char(l_l_corner);
+                    char.switch_char(l_l_corner);
                 }
                 else if x == end.x && y == end.y {
-                    char[index].switch_char(u_r_corner);
+                    char.switch_char(u_r_corner);
                 }
                 else if x == start.x && y == end.y {
-                    char[index].switch_char(u_l_corner);
+                    char.switch_char(u_l_corner);
                 }
                 else if x == end.x && y == start.y {
-                    char[index].switch_char(l_r_corner);
+                    char.switch_char(l_r_corner);
                 }
                 else if x == start.x || x == end.x {
-                    char[index].switch_char(v_line);
+                    char.switch_char(v_line);
                 }
                 else if y == start.y || y == end.y {
-                    char[index].switch_char(h_line);
+                    char.switch_char(h_line);
                 }
             }
         }
     }
 
     pub fn write_from_to(&mut self, start: Vector2, end: Vector2, c: char) {
-        let char = self.quads.as_mut_slice();
         for x in start.x..end.x {
             for y in start.y..end.y {
-                char[(x + y * self.width as i32) as usize].switch_char(c);
+                if let Some(index) = self.get_index(x, y) {
+                    self.quads[index].switch_char(c);
+                }
             }
         }
     }
 
     pub fn inverse_color_at(&mut self, pos: Vector2) {
-        let quad = &mut self.quads.as_mut_slice()[(pos.x + pos.y * self.width as i32) as usize];
-        std::mem::swap(&mut quad.fg_color, &mut quad.bg_color);
+        if let Some(index) = self.get_index(pos.x, pos.y) {
+            let quad = &mut self.quads[index];
+            std::mem::swap(&mut quad.fg_color, &mut quad.bg_color);
+        }
     }
 
     pub fn inverse_color_from_to(&mut self, start: Vector2, end: Vector2) {
         for x in start.x..end.x {
             for y in start.y..end.y {
-                let quad = &mut self.quads.as_mut_slice()[(x + y * self.width as i32) as usize];
-                std::mem::swap(&mut quad.fg_color, &mut quad.bg_color);
+                self.inverse_color_at(Vector2::new(x, y));
             }
         }
     }
 
     pub fn set_fg_at(&mut self, pos: Vector2, color: [f32;4]) {
-        self.quads.as_mut_slice()[(pos.x + pos.y * self.width as i32) as usize].switch_fg_color(color);
+        if let Some(index) = self.get_index(pos.x, pos.y) {
+            self.quads[index].switch_fg_color(color);
+        }
     }
 
     pub fn set_fg_from_to(&mut self, start: Vector2, end: Vector2, color: [f32;4]) {
         for x in start.x..end.x {
             for y in start.y..end.y {
-                self.quads.as_mut_slice()[(x + y * self.width as i32) as usize].switch_fg_color(color);
+                self.set_fg_at(Vector2::new(x, y), color);
             }
         }
     }
 
     pub fn set_bg_at(&mut self, pos: Vector2, color: [f32;4]) {
-        self.quads.as_mut_slice()[(pos.x + pos.y * self.width as i32) as usize].switch_bg_color(color);
+        if let Some(index) = self.get_index(pos.x, pos.y) {
+            self.quads[index].switch_bg_color(color);
+        }
     }
 
     pub fn set_bg_from_to(&mut self, start: Vector2, end: Vector2, color: [f32;4]) {
         for x in start.x..end.x {
             for y in start.y..end.y {
-                self.quads.as_mut_slice()[(x + y * self.width as i32) as usize].switch_bg_color(color);
+                self.set_bg_at(Vector2::new(x, y), color);
+            }
+        }
+    }
+
+    pub fn set_attributes_at(&mut self, pos: Vector2, attributes: u32) {
+        if let Some(index) = self.get_index(pos.x, pos.y) {
+            self.quads[index].switch_attributes(attributes);
+        }
+    }
+
+    pub fn get_named_color(&self, name: &str) -> Option<RGBA8> {
+        self.named_colors.get(name).copied()
+    }
+
+    pub fn set_named_color(&mut self, name: String, color: RGBA8) {
+        self.named_colors.insert(name, color);
+    }
+
+    pub fn dim(&mut self, factor: f32) {
+        for quad in self.quads.as_mut_slice() {
+            let [r, g, b, a] = quad.fg_color;
+            quad.switch_fg_color([r * factor, g * factor, b * factor, a]);
+            let [r, g, b, a] = quad.bg_color;
+            quad.switch_bg_color([r * factor, g * factor, b * factor, a]);
+        }
+    }
+
+    // cells of the current frame, kept to blend them into a later one
+    pub fn get_quads(&self) -> &[Quad] {
+        &self.quads
+    }
+
+    // mix previous cells over the grid, amount gives for each cell how much of the grid is shown
+    // glyphs cannot be mixed, the new one replaces the old one halfway
+    pub fn blend_from(&mut self, from: &[Quad], amount: impl Fn(Vector2) -> f32) {
+        let width = self.width as i32;
+        for (index, (quad, old)) in self.quads.iter_mut().zip(from).enumerate() {
+            let t = amount(Vector2::new(index as i32 % width, index as i32 / width)).clamp(0.0, 1.0);
+            quad.switch_fg_color(mix(old.fg_color, quad.fg_color, t));
+            quad.switch_bg_color(mix(old.bg_color, quad.bg_color, t));
+            if t < 0.5 {
+                quad.switch_char(old.char);
+                quad.switch_attributes(old.attributes);
             }
         }
     }
@@ -338,4 +494,32 @@ impl Grid {
             quad.switch_bg_color(bg_color);
         }
     }
+}
+
+// the buffers are given back when the grid is replaced, on a resize or with its scene
+impl Drop for Grid {
+    fn drop(&mut self) {
+        let buffers = [
+            self.vertex_position_buffer,
+            self.texture_coordinate_buffer,
+            self.fg_color_buffer,
+            self.bg_color_buffer,
+            self.cell_coordinate_buffer,
+            self.attributes_buffer,
+            self.indices_buffer,
+        ];
+        unsafe {
+            gl::DeleteBuffers(buffers.len() as i32, buffers.as_ptr());
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+fn mix(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        from[2] + (to[2] - from[2]) * t,
+        from[3] + (to[3] - from[3]) * t,
+    ]
 }
\ No newline at end of file
@@ -1,8 +1,16 @@
+// attributes of a quad, combined as a bitmask, also defined in the fragment shader
+pub const BOLD: u32 = 1;
+pub const ITALIC: u32 = 2;
+pub const UNDERLINE: u32 = 4;
+pub const BLINK: u32 = 8;
+pub const REVERSE: u32 = 16;
+
 #[derive(Copy, Clone)]
 pub struct Quad {
     pub(crate) char: char,
     pub(crate) fg_color: [f32; 4],
     pub(crate) bg_color: [f32; 4],
+    pub(crate) attributes: u32,
 }
 
 impl Quad {
@@ -18,4 +26,8 @@ impl Quad {
     pub fn switch_bg_color(&mut self, color: [f32; 4]){
         self.bg_color = color;
     }
+    #[inline]
+    pub fn switch_attributes(&mut self, attributes: u32){
+        self.attributes = attributes;
+    }
 }
\ No newline at end of file
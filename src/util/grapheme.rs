@@ -0,0 +1,22 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// user perceived character, a base character with its combining marks or joined sequence
+pub struct Grapheme<'a> {
+    pub text: &'a str,
+    // cells taken on the grid
+    pub width: i32,
+}
+
+pub fn graphemes(text: &str) -> Vec<Grapheme<'_>> {
+    text.graphemes(true).map(|text| Grapheme { text, width: cell_width(text) }).collect()
+}
+
+// a cluster without width, like a lone combining mark, still takes a cell so it can be seen and hovered
+pub fn cell_width(grapheme: &str) -> i32 {
+    (grapheme.width() as i32).clamp(1, 2)
+}
+
+pub fn display_width(text: &str) -> i32 {
+    graphemes(text).iter().map(|grapheme| grapheme.width).sum()
+}
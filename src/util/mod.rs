@@ -1,3 +1,4 @@
 pub mod rgba8;
 pub mod vector2;
 pub mod uv_layout;
+pub mod grapheme;